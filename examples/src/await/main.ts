@@ -0,0 +1,65 @@
+import { AwaitDemo, AwaitStatus, run } from "../views";
+
+type Request = {
+  promise: Promise<string>;
+  resolve: (value: string) => void;
+  reject: (error: Error) => void;
+};
+
+function request(): Request {
+  let resolve: (value: string) => void = () => {};
+  let reject: (error: Error) => void = () => {};
+  const promise = new Promise<string>((res, rej) => {
+    resolve = res;
+    reject = rej;
+  });
+  return { promise, resolve, reject };
+}
+
+export function runAwait() {
+  const settled: string[] = [];
+  const destroyed: string[] = [];
+  let updateStatus: () => void = () => {};
+
+  // The status is a separate view, as ondestroy is called during an update of the demo
+  const status = run(AwaitStatus, (update) => {
+    updateStatus = () =>
+      update((s) => ({
+        ...s,
+        settledLog: settled.join(" "),
+        destroyedLog: destroyed.join(" "),
+      }));
+    return { settledLog: "", destroyedLog: "" };
+  });
+
+  const a = request();
+  const b = request();
+  // Called after the promise is passed to the view, so that the view has already seen it
+  // settle by the time it is logged
+  const logSettled = (name: string, promise: Promise<string>) => {
+    const log = () => {
+      settled.push(name);
+      updateStatus();
+    };
+    promise.then(log, log);
+  };
+
+  const demo = run(AwaitDemo, (update) => ({
+    request: a.promise,
+    destroyed: (element) => {
+      destroyed.push(element.dataset.testid ?? "");
+      updateStatus();
+    },
+    describe: (error) => (error instanceof Error ? error.message : String(error)),
+    switchToB: () => {
+      update((s) => ({ ...s, request: b.promise }));
+      logSettled("b", b.promise);
+    },
+    resolveA: () => a.resolve("A"),
+    resolveB: () => b.resolve("B"),
+    rejectB: () => b.reject(new Error("B failed")),
+  }));
+  logSettled("a", a.promise);
+  demo.append(status);
+  return demo;
+}
@@ -0,0 +1,60 @@
+import {
+  LifecycleControls,
+  LifecycleTree,
+  run,
+  type LifecycleTreeInput,
+  type ViewState,
+} from "../views";
+
+export function runLifecycle() {
+  // Every ondestroy call is logged, so repeated or missing calls show up in the log
+  const destroyed: string[] = [];
+  let updateControls: (log: string) => void = () => {};
+
+  let treeInput: LifecycleTreeInput = {
+    rows: ["a", "b", "c"],
+    showDetails: true,
+    mode: "view",
+    destroyed: (element) => {
+      destroyed.push(element.dataset.testid ?? "");
+      updateControls(destroyed.join(" "));
+    },
+  };
+  // The tree is built directly rather than through run, so that it can be torn down
+  let tree: ViewState<LifecycleTreeInput> | undefined;
+  const updateTree = (
+    updater: (current: LifecycleTreeInput) => LifecycleTreeInput
+  ) => {
+    treeInput = updater(treeInput);
+    tree?.update(treeInput);
+  };
+
+  const controls = run(LifecycleControls, (update) => {
+    updateControls = (log) => update((s) => ({ ...s, destroyedLog: log }));
+    return {
+      destroyedLog: "",
+      removeRow: () => {
+        updateTree((s) => ({ ...s, rows: s.rows.slice(0, -1) }));
+      },
+      toggleDetails: () => {
+        updateTree((s) => ({ ...s, showDetails: !s.showDetails }));
+      },
+      switchMode: () => {
+        updateTree((s) => ({
+          ...s,
+          mode: s.mode === "view" ? "edit" : "view",
+        }));
+      },
+      teardown: () => {
+        if (tree) {
+          tree.destroy();
+          tree.root.remove();
+          tree = undefined;
+        }
+      },
+    };
+  });
+  tree = LifecycleTree(treeInput);
+  controls.append(tree.root);
+  return controls;
+}
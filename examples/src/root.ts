@@ -1,20 +1,26 @@
+import { runAwait } from "./await/main";
 import { runCafe } from "./cafe/main";
 import { runCounter } from "./counter/main";
+import { runLifecycle } from "./lifecycle/main";
 import "./style.css";
 import { runTodo } from "./todo/main";
+import { runTransitions } from "./transitions/main";
 import { Root, type View, run } from "./views";
 
 function wrapView(view: () => Element): View<{}> {
   return () => {
     const root = view();
-    return { root, update: (_: {}) => {} };
+    return { root, update: (_: {}) => {}, destroy: () => {} };
   };
 }
 
 const components: { [example: string]: View<{}> } = {
+  await: wrapView(runAwait),
   cafe: wrapView(runCafe),
   counter: wrapView(runCounter),
+  lifecycle: wrapView(runLifecycle),
   todo: wrapView(runTodo),
+  transitions: wrapView(runTransitions),
 };
 
 const component: View<{}> =
@@ -23,9 +29,12 @@ const component: View<{}> =
 function runRoot() {
   return run(Root, (update) => {
     return {
+      runAwait,
       runCafe,
       runCounter,
+      runLifecycle,
       runTodo,
+      runTransitions,
       component,
       setExample: (name) => () => {
         document.location.hash = name;
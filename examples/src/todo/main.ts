@@ -44,11 +44,10 @@ export function runTodo() {
         addTodo();
       },
 
-      updateNewTodoText: (event: Event) => {
-        const target = event.target as HTMLInputElement;
+      setNewTodoText: (text: string) => {
         update((current) => ({
           ...current,
-          newTodoText: target.value,
+          newTodoText: text,
         }));
       },
 
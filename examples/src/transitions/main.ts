@@ -0,0 +1,66 @@
+import {
+  TransitionsDemo,
+  TransitionsStatus,
+  run,
+  type Transition,
+} from "../views";
+
+export function runTransitions() {
+  const entered: string[] = [];
+  const destroyed: string[] = [];
+  let pendingLeaves: (() => void)[] = [];
+  let updateStatus: () => void = () => {};
+
+  // Leaving elements are held until "Finish leaves" is clicked, so that what happens
+  // in between can be checked
+  const hold: Transition = {
+    enter: (element) => {
+      entered.push((element as HTMLElement).dataset.testid ?? "");
+      updateStatus();
+    },
+    leave: (_element, done) => {
+      pendingLeaves.push(done);
+      updateStatus();
+    },
+  };
+
+  // The status is a separate view, as ondestroy is called during an update of the demo
+  const status = run(TransitionsStatus, (update) => {
+    updateStatus = () =>
+      update((s) => ({
+        ...s,
+        enteredLog: entered.join(" "),
+        destroyedLog: destroyed.join(" "),
+        pendingLeaves: pendingLeaves.length,
+      }));
+    return { enteredLog: "", destroyedLog: "", pendingLeaves: 0 };
+  });
+
+  let nextRow = 4;
+  const demo = run(TransitionsDemo, (update) => ({
+    rows: ["1", "2", "3"],
+    showPanel: true,
+    hold,
+    destroyed: (element) => {
+      destroyed.push(element.dataset.testid ?? "");
+      updateStatus();
+    },
+    addRow: () => {
+      update((s) => ({ ...s, rows: [...s.rows, String(nextRow++)] }));
+    },
+    removeRow: () => {
+      update((s) => ({ ...s, rows: s.rows.slice(0, -1) }));
+    },
+    togglePanel: () => {
+      update((s) => ({ ...s, showPanel: !s.showPanel }));
+    },
+    finishLeaves: () => {
+      const leaves = pendingLeaves;
+      pendingLeaves = [];
+      leaves.forEach((done) => done());
+      updateStatus();
+    },
+  }));
+  demo.append(status);
+  return demo;
+}
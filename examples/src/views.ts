@@ -26,21 +26,50 @@ function lookup<V>(m: { [k: string]: V }, k: string, d: V) {
   return m[k] ?? d;
 }
 
-type ViewState<Input> = {
+export type ViewState<Input> = {
   root: any;
   update: (input: Input) => void;
+  // Release the view and its children; called before the view is removed
+  destroy: () => void;
 };
 export type View<Input> = (input: Input) => ViewState<Input>;
+// Opt-in hooks for <for>, <if> and <switch>, e.g. to run CSS animations
+export type Transition = {
+  // Called after an element is inserted by an update
+  enter: (element: Element) => void;
+  // Called before an element is removed; it stays in the document until done() is called
+  leave: (element: Element, done: () => void) => void;
+};
+// Placeholders such as empty branches are not elements, so they skip the hooks
+function enterNode(node: ChildNode, transition: Transition) {
+  if (node instanceof Element) {
+    transition.enter(node);
+  }
+}
+function leaveNode(node: ChildNode, transition: Transition) {
+  if (node instanceof Element) {
+    transition.leave(node, () => node.remove());
+  } else {
+    node.remove();
+  }
+}
+function replaceRoot(oldRoot: ChildNode, newRoot: ChildNode, transition: Transition) {
+  oldRoot.before(newRoot);
+  enterNode(newRoot, transition);
+  leaveNode(oldRoot, transition);
+}
 function updateForLoop<Input>({
   anchor,
   prevStates,
   nextInputs,
   subView,
+  transition,
 }: {
   anchor: Comment;
   prevStates: ViewState<Input>[];
   nextInputs: Input[];
   subView: View<Input>;
+  transition?: Transition;
 }) {
   const parent = anchor.parentNode!;
   let states = prevStates.slice();
@@ -48,7 +77,12 @@ function updateForLoop<Input>({
   // Remove extra items (from end, working backwards)
   while (states.length > nextInputs.length) {
     const removed = states.pop()!;
-    parent.removeChild(removed.root);
+    removed.destroy();
+    if (transition) {
+      leaveNode(removed.root, transition);
+    } else {
+      parent.removeChild(removed.root);
+    }
   }
 
   // Update existing items
@@ -60,11 +94,91 @@ function updateForLoop<Input>({
   for (let i = states.length; i < nextInputs.length; i++) {
     const state = subView(nextInputs[i]);
     parent.insertBefore(state.root, anchor);
+    if (transition) {
+      enterNode(state.root, transition);
+    }
     states.push(state);
   }
 
   return states;
 }
+// Used by <await>: shows the branch for the latest promise, ignoring superseded ones
+type AwaitBranch<Input> = {
+  view: View<any>;
+  input: (input: Input, value?: unknown) => any;
+};
+function awaitPromise<Input>(
+  promise: Promise<unknown>,
+  input: Input,
+  branches: {
+    pending?: AwaitBranch<Input>;
+    then?: AwaitBranch<Input>;
+    catch?: AwaitBranch<Input>;
+  }
+) {
+  let currentPromise: Promise<unknown> | undefined = promise;
+  let currentInput = input;
+  let settled: { branch: "then" | "catch"; value: unknown } | undefined;
+  const currentBranch = () => (settled ? branches[settled.branch] : branches.pending);
+  const build = (): ViewState<any> => {
+    const branch = currentBranch();
+    return branch
+      ? branch.view(branch.input(currentInput, settled?.value))
+      : { root: document.createComment("await-empty"), update: () => {}, destroy: () => {} };
+  };
+  let state = build();
+  const show = () => {
+    const next = build();
+    state.destroy();
+    state.root.replaceWith(next.root);
+    state = next;
+  };
+  const subscribe = (promise: Promise<unknown>) => {
+    const settle = (branch: "then" | "catch") => (value: unknown) => {
+      if (promise === currentPromise) {
+        settled = { branch, value };
+        show();
+      }
+    };
+    promise.then(settle("then"), settle("catch"));
+  };
+  subscribe(promise);
+  return {
+    get root() {
+      return state.root;
+    },
+    update(input: Input, promise: Promise<unknown>) {
+      currentInput = input;
+      if (promise !== currentPromise) {
+        currentPromise = promise;
+        settled = undefined;
+        show();
+        subscribe(promise);
+      } else {
+        const branch = currentBranch();
+        if (branch) {
+          state.update(branch.input(input, settled?.value));
+        }
+      }
+    },
+    destroy() {
+      currentPromise = undefined;
+      state.destroy();
+    },
+  };
+}
+// Used by <raw>: the content is inserted as HTML without escaping
+function parseRawHtml(html: string): Node[] {
+  const template = document.createElement("template");
+  template.innerHTML = html;
+  return Array.from(template.content.childNodes);
+}
+function replaceRawHtml(start: Comment, end: Comment, html: string) {
+  while (start.nextSibling && start.nextSibling !== end) {
+    start.nextSibling.remove();
+  }
+  start.after(...parseRawHtml(html));
+}
 export function run<Input>(
   view: View<Input>,
   buildComponent: (
@@ -85,8 +199,111 @@ export function run<Input>(
 
   return state.root;
 }
-export type CartInput = { cart: { getItems: (v0: any, v1: any) => { lineTotal: any, name: string, qty: number }[], grandTotal: (v0: any, v1: any) => any, items: any, subtotal: (v0: any, v1: any) => any, totalQty: (v0: any) => number }, categories: any, currency: (v0: any) => string };
-export function Cart(input: CartInput): ViewState<CartInput> {
+export type AwaitDemoInput = { describe: (v0: unknown) => string, destroyed: (v0: HTMLElementTagNameMap["div"]) => void, rejectB: (this: GlobalEventHandlers, ev: MouseEvent) => any, request: Promise<string>, resolveA: (this: GlobalEventHandlers, ev: MouseEvent) => any, resolveB: (this: GlobalEventHandlers, ev: MouseEvent) => any, switchToB: (this: GlobalEventHandlers, ev: MouseEvent) => any };
+export function AwaitDemo(input: AwaitDemoInput): ViewState<AwaitDemoInput> {
+  const child0: View<any> = (input) => {
+    const node0 = h("div", {className: "p-4"}, [t("Loading...")], {testid: "pending"});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const child1: View<any> = (input) => {
+    const node0 = t(input.value);
+    const node1 = h("div", {className: "p-4"}, [node0], {testid: "result"});
+    const root = node1;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        if (input.value !== currentInput.value) {
+          node0.textContent = input.value;
+        }
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node1);
+      }
+    };
+  };
+  const child2: View<any> = (input) => {
+    const node0 = t(input.describe(input.error));
+    const node1 = h("div", {className: "p-4"}, [node0], {testid: "error"});
+    const root = node1;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        if (input.describe !== currentInput.describe || input.error !== currentInput.error) {
+          node0.textContent = input.describe(input.error);
+        }
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node1);
+      }
+    };
+  };
+  const awaitState0 = awaitPromise(input.request, input, { pending: { view: child0, input: (input) => input }, then: { view: child1, input: (input, value) => ({ ...input, value: value }) }, catch: { view: child2, input: (input, value) => ({ ...input, error: value }) } });
+  const node0 = h("button", {className: "px-4 py-2", onclick: input.switchToB}, [t("Request B")], {testid: "switch-to-b-btn"});
+  const node1 = h("button", {className: "px-4 py-2", onclick: input.resolveA}, [t("Resolve A")], {testid: "resolve-a-btn"});
+  const node2 = h("button", {className: "px-4 py-2", onclick: input.resolveB}, [t("Resolve B")], {testid: "resolve-b-btn"});
+  const node3 = h("button", {className: "px-4 py-2", onclick: input.rejectB}, [t("Reject B")], {testid: "reject-b-btn"});
+  const root = h("div", {}, [h("h1", {className: "p-4"}, [t("Await Example")]), h("div", {className: "flex gap-2 p-4"}, [node0, node1, node2, node3]), awaitState0.root], {testid: "await-app"});
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.rejectB !== currentInput.rejectB) {
+        node3["onclick"] = input.rejectB;
+      }
+      if (input.resolveA !== currentInput.resolveA) {
+        node1["onclick"] = input.resolveA;
+      }
+      if (input.resolveB !== currentInput.resolveB) {
+        node2["onclick"] = input.resolveB;
+      }
+      if (input.switchToB !== currentInput.switchToB) {
+        node0["onclick"] = input.switchToB;
+      }
+      awaitState0.update(input, input.request);
+      currentInput = input;
+    },
+    destroy() {
+      awaitState0.destroy();
+    }
+  };
+}
+export type AwaitStatusInput = { destroyedLog: string, settledLog: string };
+export function AwaitStatus(input: AwaitStatusInput): ViewState<AwaitStatusInput> {
+  const node0 = t(input.settledLog);
+  const node1 = t(input.destroyedLog);
+  const root = h("div", {className: "p-4"}, [h("p", {}, [node0], {testid: "settled-log"}), h("p", {}, [node1], {testid: "destroyed-log"})]);
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.destroyedLog !== currentInput.destroyedLog) {
+        node1.textContent = input.destroyedLog;
+      }
+      if (input.settledLog !== currentInput.settledLog) {
+        node0.textContent = input.settledLog;
+      }
+      currentInput = input;
+    },
+    destroy() {}
+  };
+}
+export type CartInput<T = any, U = any, V = any> = { cart: CartInputCart<T, U, V>, categories: T, currency: (v0: V) => string };
+export type CartInputCart<T = any, U = any, V = any> = { getItems: (v0: T, v1: U) => { lineTotal: V, name: string, qty: number }[], grandTotal: (v0: T, v1: U) => V, items: U, subtotal: (v0: T, v1: U) => V, totalQty: (v0: U) => number };
+export function Cart<T = any, U = any, V = any>(input: CartInput<T, U, V>): ViewState<CartInput<T, U, V>> {
   const child0: View<any> = (input) => {
     const node0 = t(input.ci.name);
     const node1 = t(numberToString(input.ci.qty));
@@ -108,7 +325,8 @@ export function Cart(input: CartInput): ViewState<CartInput> {
           node1.textContent = numberToString(input.ci.qty);
         }
         currentInput = input;
-      }
+      },
+      destroy() {}
     };
   };
   const anchor0 = document.createComment("for-loop-0");
@@ -144,11 +362,17 @@ export function Cart(input: CartInput): ViewState<CartInput> {
         subView: child0
       });
       currentInput = input;
+    },
+    destroy() {
+      childState0.forEach((state) => state.destroy());
     }
   };
 }
-export type MenuInput = { cart: { decrement: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, increment: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, items: any }, categories: { items: { id: string, name: string, price: any }[], name: string }[], currency: (v0: any) => string };
-export function Menu(input: MenuInput): ViewState<MenuInput> {
+export type MenuInput<T = any> = { cart: MenuInputCart, categories: MenuInputCategoriesItem<T>[], currency: (v0: T) => string };
+export type MenuInputCart = { decrement: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, increment: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, items: Record<string, number> };
+export type MenuInputCategoriesItem<T = any> = { items: MenuInputCategoriesItemItemsItem<T>[], name: string };
+export type MenuInputCategoriesItemItemsItem<T = any> = { id: string, name: string, price: T };
+export function Menu<T = any>(input: MenuInput<T>): ViewState<MenuInput<T>> {
   const child0: View<any> = (input) => {
     const child0: View<any> = (input) => {
       const node0 = t(input.item.name);
@@ -179,7 +403,8 @@ export function Menu(input: MenuInput): ViewState<MenuInput> {
             node3.dataset["testid"] = `remove-${input.item.name}`;
           }
           currentInput = input;
-        }
+        },
+        destroy() {}
       };
     };
     const anchor0 = document.createComment("for-loop-0");
@@ -207,6 +432,9 @@ export function Menu(input: MenuInput): ViewState<MenuInput> {
           subView: child0
         });
         currentInput = input;
+      },
+      destroy() {
+        childState0.forEach((state) => state.destroy());
       }
     };
   };
@@ -231,10 +459,14 @@ export function Menu(input: MenuInput): ViewState<MenuInput> {
         subView: child0
       });
       currentInput = input;
+    },
+    destroy() {
+      childState0.forEach((state) => state.destroy());
     }
   };
 }
-export type OrderInput = { order: { details: { address: string, type: "delivery" } | { table: string, type: "dinein" } | { type: "pickup" }, selectOrder: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, updateAddress: (this: GlobalEventHandlers, ev: Event) => any, updateTable: (this: GlobalEventHandlers, ev: Event) => any } };
+export type OrderInput = { order: OrderInputOrder };
+export type OrderInputOrder = { details: { address: string, type: "delivery" } | { table: string, type: "dinein" } | { type: "pickup" }, selectOrder: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, updateAddress: (this: GlobalEventHandlers, ev: Event) => any, updateTable: (this: GlobalEventHandlers, ev: Event) => any };
 export function Order(input: OrderInput): ViewState<OrderInput> {
   const child0: View<any> = (input) => {
     const root = t("\n      ");
@@ -243,7 +475,8 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
       root,
       update(input) {
         currentInput = input;
-      }
+      },
+      destroy() {}
     };
   };
   const child1: View<any> = (input) => {
@@ -260,7 +493,8 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
           node0["oninput"] = input.order.updateTable;
         }
         currentInput = input;
-      }
+      },
+      destroy() {}
     };
   };
   const child2: View<any> = (input) => {
@@ -277,7 +511,8 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
           node0["oninput"] = input.order.updateAddress;
         }
         currentInput = input;
-      }
+      },
+      destroy() {}
     };
   };
   let currentSwitchState0: ViewState<any>;
@@ -303,7 +538,7 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
         return st.root;
       }
       default: {
-        const st = { root: document.createComment("switch-empty"), update: (_: any) => {} };
+        const st = { root: document.createComment("switch-empty"), update: (_: any) => {}, destroy: () => {} };
         currentSwitchState0 = st;
         return st.root;
       }
@@ -347,10 +582,11 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
             break;
           }
           default: {
-            newState0 = { root: document.createComment("switch-empty"), update: (_: any) => {} };
+            newState0 = { root: document.createComment("switch-empty"), update: (_: any) => {}, destroy: () => {} };
             newRoot0 = newState0.root;
           }
         }
+        currentSwitchState0.destroy();
         currentSwitchState0.root.replaceWith(newRoot0);
         currentSwitchState0 = newState0;
       } else {
@@ -376,11 +612,18 @@ export function Order(input: OrderInput): ViewState<OrderInput> {
         }
       }
       currentInput = input;
+    },
+    destroy() {
+      currentSwitchState0.destroy();
     }
   };
 }
-export type CafeInput = { cart: { decrement: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, getItems: (v0: { items: { id: string, name: string, price: any }[], name: string }[], v1: any) => { lineTotal: any, name: string, qty: number }[], grandTotal: (v0: { items: { id: string, name: string, price: any }[], name: string }[], v1: any) => any, increment: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, items: any, subtotal: (v0: { items: { id: string, name: string, price: any }[], name: string }[], v1: any) => any, totalQty: (v0: any) => number }, categories: { items: { id: string, name: string, price: any }[], name: string }[], currency: (v0: any) => string, order: { details: { address: string, type: "delivery" } | { table: string, type: "dinein" } | { type: "pickup" }, selectOrder: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, updateAddress: (this: GlobalEventHandlers, ev: Event) => any, updateTable: (this: GlobalEventHandlers, ev: Event) => any } };
-export function Cafe(input: CafeInput): ViewState<CafeInput> {
+export type CafeInput<T = any> = { cart: CafeInputCart<T>, categories: CafeInputCategoriesItem<T>[], currency: (v0: T) => string, order: CafeInputOrder };
+export type CafeInputCart<T = any> = { decrement: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, getItems: (v0: CafeInputCategoriesItem<T>[], v1: Record<string, number>) => { lineTotal: T, name: string, qty: number }[], grandTotal: (v0: CafeInputCategoriesItem<T>[], v1: Record<string, number>) => T, increment: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, items: Record<string, number>, subtotal: (v0: CafeInputCategoriesItem<T>[], v1: Record<string, number>) => T, totalQty: (v0: Record<string, number>) => number };
+export type CafeInputCategoriesItem<T = any> = { items: CafeInputCategoriesItemItemsItem<T>[], name: string };
+export type CafeInputCategoriesItemItemsItem<T = any> = { id: string, name: string, price: T };
+export type CafeInputOrder = { details: { address: string, type: "delivery" } | { table: string, type: "dinein" } | { type: "pickup" }, selectOrder: (v0: string) => (this: GlobalEventHandlers, ev: MouseEvent) => any, updateAddress: (this: GlobalEventHandlers, ev: Event) => any, updateTable: (this: GlobalEventHandlers, ev: Event) => any };
+export function Cafe<T = any>(input: CafeInput<T>): ViewState<CafeInput<T>> {
   const componentState0 = Menu({cart: input.cart, categories: input.categories, currency: input.currency});
   const componentState1 = Order({order: input.order});
   const componentState2 = Cart({cart: input.cart, categories: input.categories, currency: input.currency});
@@ -393,6 +636,11 @@ export function Cafe(input: CafeInput): ViewState<CafeInput> {
       componentState1.update({order: input.order});
       componentState2.update({cart: input.cart, categories: input.categories, currency: input.currency});
       currentInput = input;
+    },
+    destroy() {
+      componentState0.destroy();
+      componentState1.destroy();
+      componentState2.destroy();
     }
   };
 }
@@ -412,6 +660,242 @@ export function Counter(input: CounterInput): ViewState<CounterInput> {
         node0.textContent = numberToString(input.count);
       }
       currentInput = input;
+    },
+    destroy() {}
+  };
+}
+export type LifecycleControlsInput = { destroyedLog: string, removeRow: (this: GlobalEventHandlers, ev: MouseEvent) => any, switchMode: (this: GlobalEventHandlers, ev: MouseEvent) => any, teardown: (this: GlobalEventHandlers, ev: MouseEvent) => any, toggleDetails: (this: GlobalEventHandlers, ev: MouseEvent) => any };
+export function LifecycleControls(input: LifecycleControlsInput): ViewState<LifecycleControlsInput> {
+  const node0 = h("button", {className: "px-4 py-2", onclick: input.removeRow}, [t("Remove row")], {testid: "remove-row-btn"});
+  const node1 = h("button", {className: "px-4 py-2", onclick: input.toggleDetails}, [t("Toggle details")], {testid: "toggle-details-btn"});
+  const node2 = h("button", {className: "px-4 py-2", onclick: input.switchMode}, [t("Switch mode")], {testid: "switch-mode-btn"});
+  const node3 = h("button", {className: "px-4 py-2", onclick: input.teardown}, [t("Tear down")], {testid: "teardown-btn"});
+  const node4 = t(input.destroyedLog);
+  const root = h("div", {}, [h("h1", {className: "p-4"}, [t("Lifecycle Example")]), h("div", {className: "flex gap-2 p-4"}, [node0, node1, node2, node3]), h("p", {className: "p-4"}, [node4], {testid: "destroyed-log"})], {testid: "lifecycle-app"});
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.destroyedLog !== currentInput.destroyedLog) {
+        node4.textContent = input.destroyedLog;
+      }
+      if (input.removeRow !== currentInput.removeRow) {
+        node0["onclick"] = input.removeRow;
+      }
+      if (input.switchMode !== currentInput.switchMode) {
+        node2["onclick"] = input.switchMode;
+      }
+      if (input.teardown !== currentInput.teardown) {
+        node3["onclick"] = input.teardown;
+      }
+      if (input.toggleDetails !== currentInput.toggleDetails) {
+        node1["onclick"] = input.toggleDetails;
+      }
+      currentInput = input;
+    },
+    destroy() {}
+  };
+}
+export type LifecycleLeafInput = { destroyed: (v0: HTMLElementTagNameMap["div"]) => void, name: string };
+export function LifecycleLeaf(input: LifecycleLeafInput): ViewState<LifecycleLeafInput> {
+  const node0 = t(input.name);
+  const node1 = h("div", {}, [node0], {testid: input.name});
+  const root = node1;
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.name !== currentInput.name) {
+        node0.textContent = input.name;
+        node1.dataset["testid"] = input.name;
+      }
+      currentInput = input;
+    },
+    destroy() {
+      (currentInput.destroyed)(node1);
+    }
+  };
+}
+export type LifecycleTreeInput = { destroyed: (v0: HTMLElementTagNameMap["div"]) => void, mode: "edit" | "view", rows: string[], showDetails: boolean };
+export function LifecycleTree(input: LifecycleTreeInput): ViewState<LifecycleTreeInput> {
+  const child0: View<any> = (input) => {
+    const componentState0 = LifecycleLeaf({destroyed: input.destroyed, name: `leaf-${input.row}`});
+    const node0 = h("div", {}, [componentState0.root], {testid: `row-${input.row}`});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        if (input.row !== currentInput.row) {
+          node0.dataset["testid"] = `row-${input.row}`;
+        }
+        componentState0.update({destroyed: input.destroyed, name: `leaf-${input.row}`});
+        currentInput = input;
+      },
+      destroy() {
+        componentState0.destroy();
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const child1: View<any> = (input) => {
+    const node0 = h("div", {}, [t("Details")], {testid: "details"});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const child2: View<any> = (input) => {
+    const componentState0 = LifecycleLeaf({destroyed: input.destroyed, name: `viewing`});
+    const root = componentState0.root;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        componentState0.update({destroyed: input.destroyed, name: `viewing`});
+        currentInput = input;
+      },
+      destroy() {
+        componentState0.destroy();
+      }
+    };
+  };
+  const child3: View<any> = (input) => {
+    const node0 = h("div", {}, [t("Editing")], {testid: "editing"});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const anchor0 = document.createComment("for-loop-0");
+  const loopElements0 = [];
+  let childState0: any[] = [];
+  for (const item of input.rows) {
+    const itemState = child0({ ...input, row: item });
+    loopElements0.push(itemState.root);
+    childState0.push(itemState);
+  }
+  loopElements0.push(anchor0);
+  let currentState0: ViewState<any>;
+  if (input.showDetails) {
+    currentState0 = child1(input);
+  } else {
+    currentState0 = { root: document.createComment("empty"), update: (_: any) => {}, destroy: () => {} };
+  }
+  const conditionalElement0 = currentState0.root;
+  let currentSwitchState0: ViewState<any>;
+  const switchElement0 = (() => {
+    const onValue = input.mode;
+    switch (onValue) {
+      case "view": {
+        const caseInput = input;
+        const st = child2(caseInput);
+        currentSwitchState0 = st;
+        return st.root;
+      }
+      case "edit": {
+        const caseInput = input;
+        const st = child3(caseInput);
+        currentSwitchState0 = st;
+        return st.root;
+      }
+      default: {
+        const st = { root: document.createComment("switch-empty"), update: (_: any) => {}, destroy: () => {} };
+        currentSwitchState0 = st;
+        return st.root;
+      }
+    }
+  })();
+  const node0 = h("div", {className: "p-4"}, [...loopElements0, conditionalElement0, switchElement0], {testid: "tree"});
+  const root = node0;
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      childState0 = updateForLoop({
+        anchor: anchor0,
+        prevStates: childState0,
+        nextInputs: input.rows.map((row: any) => ({ ...input, row })),
+        subView: child0
+      });
+      if (input.showDetails !== currentInput.showDetails) {
+        let newState0: ViewState<any>;
+        if (input.showDetails) {
+          newState0 = child1(input);
+        } else {
+          newState0 = { root: document.createComment("empty"), update: (_: any) => {}, destroy: () => {} };
+        }
+        const newRoot0 = newState0.root;
+        currentState0.destroy();
+        currentState0.root.replaceWith(newRoot0);
+        currentState0 = newState0;
+      } else {
+        currentState0.update(input);
+      }
+      const newOnValue0 = input.mode;
+      const prevOnValue0 = currentInput.mode;
+      if (newOnValue0 !== prevOnValue0) {
+        let newState0: ViewState<any>;
+        let newRoot0: any;
+        switch (newOnValue0) {
+          case "view": {
+            const caseInput = input;
+            newState0 = child2(caseInput);
+            newRoot0 = newState0.root;
+            break;
+          }
+          case "edit": {
+            const caseInput = input;
+            newState0 = child3(caseInput);
+            newRoot0 = newState0.root;
+            break;
+          }
+          default: {
+            newState0 = { root: document.createComment("switch-empty"), update: (_: any) => {}, destroy: () => {} };
+            newRoot0 = newState0.root;
+          }
+        }
+        currentSwitchState0.destroy();
+        currentSwitchState0.root.replaceWith(newRoot0);
+        currentSwitchState0 = newState0;
+      } else {
+        switch (newOnValue0) {
+          case "view": {
+            const caseInput = input;
+            currentSwitchState0.update(caseInput);
+            break;
+          }
+          case "edit": {
+            const caseInput = input;
+            currentSwitchState0.update(caseInput);
+            break;
+          }
+          default: {
+            // no-op
+          }
+        }
+      }
+      currentInput = input;
+    },
+    destroy() {
+      childState0.forEach((state) => state.destroy());
+      currentState0.destroy();
+      currentSwitchState0.destroy();
+      (currentInput.destroyed)(node0);
     }
   };
 }
@@ -421,7 +905,10 @@ export function Root(input: RootInput): ViewState<RootInput> {
   const node0 = h("button", {className: "w-30 py-2", onclick: input.setExample(`cafe`)}, [t("Cafe")]);
   const node1 = h("button", {className: "w-30 py-2", onclick: input.setExample(`todo`)}, [t("Todo")]);
   const node2 = h("button", {className: "w-30 py-2", onclick: input.setExample(`counter`)}, [t("Counter")]);
-  const root = h("div", {}, [h("div", {className: "flex gap-2 bg-blue-50 p-4"}, [node0, node1, node2]), useViewState0.root]);
+  const node3 = h("button", {className: "w-30 py-2", onclick: input.setExample(`lifecycle`)}, [t("Lifecycle")]);
+  const node4 = h("button", {className: "w-30 py-2", onclick: input.setExample(`transitions`)}, [t("Transitions")]);
+  const node5 = h("button", {className: "w-30 py-2", onclick: input.setExample(`await`)}, [t("Await")]);
+  const root = h("div", {}, [h("div", {className: "flex gap-2 bg-blue-50 p-4"}, [node0, node1, node2, node3, node4, node5]), useViewState0.root]);
   let currentInput = input;
   return {
     root,
@@ -430,20 +917,28 @@ export function Root(input: RootInput): ViewState<RootInput> {
         node0["onclick"] = input.setExample(`cafe`);
         node1["onclick"] = input.setExample(`todo`);
         node2["onclick"] = input.setExample(`counter`);
+        node3["onclick"] = input.setExample(`lifecycle`);
+        node4["onclick"] = input.setExample(`transitions`);
+        node5["onclick"] = input.setExample(`await`);
       }
       if (input.component !== currentInput.component) {
         const newUseViewState0 = input.component({});
+        useViewState0.destroy();
         useViewState0.root.replaceWith(newUseViewState0.root);
         useViewState0 = newUseViewState0;
       } else {
         useViewState0.update({});
       }
       currentInput = input;
+    },
+    destroy() {
+      useViewState0.destroy();
     }
   };
 }
-export type TodoInput = { addTodoHandler: (this: GlobalEventHandlers, ev: MouseEvent) => any, completedCount: (v0: { completed: boolean, id: any, text: string }[]) => number, deleteHandler: (v0: any) => (this: GlobalEventHandlers, ev: MouseEvent) => any, handleKeyPress: (this: GlobalEventHandlers, ev: KeyboardEvent) => any, newTodoText: string, todos: { completed: boolean, id: any, text: string }[], toggleHandler: (v0: any) => (this: GlobalEventHandlers, ev: Event) => any, totalCount: (v0: { completed: boolean, id: any, text: string }[]) => number, updateNewTodoText: (this: GlobalEventHandlers, ev: Event) => any };
-export function Todo(input: TodoInput): ViewState<TodoInput> {
+export type TodoInput<T = any> = { addTodoHandler: (this: GlobalEventHandlers, ev: MouseEvent) => any, completedCount: (v0: TodoInputTodosItem<T>[]) => number, deleteHandler: (v0: T) => (this: GlobalEventHandlers, ev: MouseEvent) => any, handleKeyPress: (this: GlobalEventHandlers, ev: KeyboardEvent) => any, newTodoText: string, setNewTodoText: (v0: string) => void, todos: TodoInputTodosItem<T>[], toggleHandler: (v0: T) => (this: GlobalEventHandlers, ev: Event) => any, totalCount: (v0: TodoInputTodosItem<T>[]) => number };
+export type TodoInputTodosItem<T = any> = { completed: boolean, id: T, text: string };
+export function Todo<T = any>(input: TodoInput<T>): ViewState<TodoInput<T>> {
   const child0: View<any> = (input) => {
     const node0 = h("input", {checked: input.todo.completed, className: "w-5 h-5 text-purple-400 accent-purple-400 cursor-pointer", onchange: input.toggleHandler(input.todo.id), type: "checkbox"}, []);
     const node1 = t(input.todo.text);
@@ -466,7 +961,8 @@ export function Todo(input: TodoInput): ViewState<TodoInput> {
           node1.textContent = input.todo.text;
         }
         currentInput = input;
-      }
+      },
+      destroy() {}
     };
   };
   const anchor0 = document.createComment("for-loop-0");
@@ -478,11 +974,12 @@ export function Todo(input: TodoInput): ViewState<TodoInput> {
     childState0.push(itemState);
   }
   loopElements0.push(anchor0);
-  const node0 = h("input", {className: "grow", oninput: input.updateNewTodoText, onkeypress: input.handleKeyPress, placeholder: "Add a new todo...", type: "text", value: input.newTodoText}, [], {testid: "todo-input"});
+  const node0 = h("input", {className: "grow", onkeypress: input.handleKeyPress, placeholder: "Add a new todo...", type: "text", value: input.newTodoText}, [], {testid: "todo-input"});
   const node1 = h("button", {className: "px-8 py-4", onclick: input.addTodoHandler}, [t("Add Todo")], {testid: "add-todo-btn"});
   const node2 = t(numberToString(input.totalCount(input.todos)));
   const node3 = t(numberToString(input.completedCount(input.todos)));
   const root = h("div", {}, [h("h1", {className: "p-4"}, [t("Todo Example")]), h("div", {className: "flex gap-3 mb-8 p-4 items-center"}, [node0, node1]), h("div", {className: "mb-8 p-4"}, [h("ul", {className: "p-0 m-0"}, [...loopElements0], {testid: "todo-list"})]), h("div", {className: "p-4"}, [h("div", {className: "flex justify-between gap-4 px-5 py-5 bg-gradient-to-r from-gray-100 to-gray-200 rounded-xl border-2 border-gray-200 min-h-5"}, [h("p", {className: "m-0 text-base font-semibold text-gray-700"}, [t("Total todos: "), node2], {testid: "total-count"}), h("p", {className: "m-0 text-base font-semibold text-gray-700"}, [t("Completed: "), node3], {testid: "completed-count"})])])], {testid: "todo-app"});
+  node0.addEventListener("input", () => currentInput.setNewTodoText(node0.value));
   let currentInput = input;
   return {
     root,
@@ -502,9 +999,6 @@ export function Todo(input: TodoInput): ViewState<TodoInput> {
       if (input.todos !== currentInput.todos || input.totalCount !== currentInput.totalCount) {
         node2.textContent = numberToString(input.totalCount(input.todos));
       }
-      if (input.updateNewTodoText !== currentInput.updateNewTodoText) {
-        node0["oninput"] = input.updateNewTodoText;
-      }
       childState0 = updateForLoop({
         anchor: anchor0,
         prevStates: childState0,
@@ -512,6 +1006,148 @@ export function Todo(input: TodoInput): ViewState<TodoInput> {
         subView: child0
       });
       currentInput = input;
+    },
+    destroy() {
+      childState0.forEach((state) => state.destroy());
     }
   };
 }
+export type TransitionsDemoInput = { addRow: (this: GlobalEventHandlers, ev: MouseEvent) => any, destroyed: (v0: HTMLElementTagNameMap["div"]) => void, finishLeaves: (this: GlobalEventHandlers, ev: MouseEvent) => any, hold: Transition, removeRow: (this: GlobalEventHandlers, ev: MouseEvent) => any, rows: string[], showPanel: boolean, togglePanel: (this: GlobalEventHandlers, ev: MouseEvent) => any };
+export function TransitionsDemo(input: TransitionsDemoInput): ViewState<TransitionsDemoInput> {
+  const child0: View<any> = (input) => {
+    const node0 = t(input.row);
+    const node1 = h("div", {}, [t("Row "), node0], {testid: `row-${input.row}`});
+    const root = node1;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        if (input.row !== currentInput.row) {
+          node0.textContent = input.row;
+          node1.dataset["testid"] = `row-${input.row}`;
+        }
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node1);
+      }
+    };
+  };
+  const child1: View<any> = (input) => {
+    const node0 = h("div", {className: "p-4"}, [t("Panel")], {testid: "panel"});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const child2: View<any> = (input) => {
+    const node0 = h("div", {className: "p-4"}, [t("No panel")], {testid: "no-panel"});
+    const root = node0;
+    let currentInput = input;
+    return {
+      root,
+      update(input) {
+        currentInput = input;
+      },
+      destroy() {
+        (currentInput.destroyed)(node0);
+      }
+    };
+  };
+  const anchor0 = document.createComment("for-loop-0");
+  const loopElements0 = [];
+  let childState0: any[] = [];
+  for (const item of input.rows) {
+    const itemState = child0({ ...input, row: item });
+    loopElements0.push(itemState.root);
+    childState0.push(itemState);
+  }
+  loopElements0.push(anchor0);
+  let currentState0: ViewState<any>;
+  if (input.showPanel) {
+    currentState0 = child1(input);
+  } else {
+    currentState0 = child2(input);
+  }
+  const conditionalElement0 = currentState0.root;
+  const node0 = h("button", {className: "px-4 py-2", onclick: input.addRow}, [t("Add row")], {testid: "add-row-btn"});
+  const node1 = h("button", {className: "px-4 py-2", onclick: input.removeRow}, [t("Remove row")], {testid: "remove-row-btn"});
+  const node2 = h("button", {className: "px-4 py-2", onclick: input.togglePanel}, [t("Toggle panel")], {testid: "toggle-panel-btn"});
+  const node3 = h("button", {className: "px-4 py-2", onclick: input.finishLeaves}, [t("Finish leaves")], {testid: "finish-leaves-btn"});
+  const root = h("div", {}, [h("h1", {className: "p-4"}, [t("Transitions Example")]), h("div", {className: "flex gap-2 p-4"}, [node0, node1, node2, node3]), h("div", {className: "p-4"}, [...loopElements0], {testid: "rows"}), conditionalElement0], {testid: "transitions-app"});
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.addRow !== currentInput.addRow) {
+        node0["onclick"] = input.addRow;
+      }
+      if (input.finishLeaves !== currentInput.finishLeaves) {
+        node3["onclick"] = input.finishLeaves;
+      }
+      if (input.removeRow !== currentInput.removeRow) {
+        node1["onclick"] = input.removeRow;
+      }
+      if (input.togglePanel !== currentInput.togglePanel) {
+        node2["onclick"] = input.togglePanel;
+      }
+      childState0 = updateForLoop({
+        anchor: anchor0,
+        prevStates: childState0,
+        nextInputs: input.rows.map((row: any) => ({ ...input, row })),
+        transition: input.hold,
+        subView: child0
+      });
+      if (input.showPanel !== currentInput.showPanel) {
+        let newState0: ViewState<any>;
+        if (input.showPanel) {
+          newState0 = child1(input);
+        } else {
+          newState0 = child2(input);
+        }
+        const newRoot0 = newState0.root;
+        currentState0.destroy();
+        replaceRoot(currentState0.root, newRoot0, input.hold);
+        currentState0 = newState0;
+      } else {
+        currentState0.update(input);
+      }
+      currentInput = input;
+    },
+    destroy() {
+      childState0.forEach((state) => state.destroy());
+      currentState0.destroy();
+    }
+  };
+}
+export type TransitionsStatusInput = { destroyedLog: string, enteredLog: string, pendingLeaves: number };
+export function TransitionsStatus(input: TransitionsStatusInput): ViewState<TransitionsStatusInput> {
+  const node0 = t(input.enteredLog);
+  const node1 = t(input.destroyedLog);
+  const node2 = t(numberToString(input.pendingLeaves));
+  const root = h("div", {className: "p-4"}, [h("p", {}, [node0], {testid: "entered-log"}), h("p", {}, [node1], {testid: "destroyed-log"}), h("p", {}, [node2], {testid: "pending-leaves"})]);
+  let currentInput = input;
+  return {
+    root,
+    update(input) {
+      if (input.destroyedLog !== currentInput.destroyedLog) {
+        node1.textContent = input.destroyedLog;
+      }
+      if (input.enteredLog !== currentInput.enteredLog) {
+        node0.textContent = input.enteredLog;
+      }
+      if (input.pendingLeaves !== currentInput.pendingLeaves) {
+        node2.textContent = numberToString(input.pendingLeaves);
+      }
+      currentInput = input;
+    },
+    destroy() {}
+  };
+}
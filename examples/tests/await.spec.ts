@@ -0,0 +1,67 @@
+import { test, expect, type Page } from "@playwright/test";
+
+test.describe("Await App", () => {
+  // The test ids of the elements whose ondestroy has been called, once per call
+  const destroyedLog = async (page: Page) => {
+    const log = await page.getByTestId("destroyed-log").textContent();
+    return (log ?? "").split(" ").filter(Boolean).sort();
+  };
+
+  test.beforeEach(async ({ page }) => {
+    await page.goto("http://localhost:5173/#await");
+  });
+
+  test("should show the result once the promise resolves", async ({
+    page,
+  }) => {
+    await expect(page.getByTestId("pending")).toBeVisible();
+
+    await page.getByTestId("resolve-a-btn").click();
+    await expect(page.getByTestId("settled-log")).toHaveText("a");
+    await expect(page.getByTestId("result")).toHaveText("A");
+    await expect(page.getByTestId("pending")).toHaveCount(0);
+  });
+
+  test("should ignore a superseded promise that resolves last", async ({
+    page,
+  }) => {
+    await page.getByTestId("switch-to-b-btn").click();
+    await page.getByTestId("resolve-b-btn").click();
+    await expect(page.getByTestId("result")).toHaveText("B");
+
+    await page.getByTestId("resolve-a-btn").click();
+    await expect(page.getByTestId("settled-log")).toHaveText("b a");
+    await expect(page.getByTestId("result")).toHaveText("B");
+  });
+
+  test("should ignore a superseded promise that resolves first", async ({
+    page,
+  }) => {
+    await page.getByTestId("switch-to-b-btn").click();
+    await page.getByTestId("resolve-a-btn").click();
+    await expect(page.getByTestId("settled-log")).toHaveText("a");
+    await expect(page.getByTestId("pending")).toBeVisible();
+    await expect(page.getByTestId("result")).toHaveCount(0);
+
+    await page.getByTestId("reject-b-btn").click();
+    await expect(page.getByTestId("error")).toHaveText("B failed");
+    await expect(page.getByTestId("pending")).toHaveCount(0);
+  });
+
+  test("should destroy the previous branch on each switch", async ({
+    page,
+  }) => {
+    await page.getByTestId("resolve-a-btn").click();
+    await expect(page.getByTestId("result")).toHaveText("A");
+    expect(await destroyedLog(page)).toEqual(["pending"]);
+
+    await page.getByTestId("switch-to-b-btn").click();
+    await expect(page.getByTestId("pending")).toBeVisible();
+    await expect(page.getByTestId("result")).toHaveCount(0);
+    expect(await destroyedLog(page)).toEqual(["pending", "result"]);
+
+    await page.getByTestId("reject-b-btn").click();
+    await expect(page.getByTestId("error")).toHaveText("B failed");
+    expect(await destroyedLog(page)).toEqual(["pending", "pending", "result"]);
+  });
+});
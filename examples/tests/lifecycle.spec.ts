@@ -0,0 +1,82 @@
+import { test, expect, type Page } from "@playwright/test";
+
+test.describe("Lifecycle App", () => {
+  // The test ids of the elements whose ondestroy has been called, once per call
+  const destroyedLog = async (page: Page) => {
+    const log = await page.getByTestId("destroyed-log").textContent();
+    return (log ?? "").split(" ").filter(Boolean).sort();
+  };
+
+  test.beforeEach(async ({ page }) => {
+    await page.goto("http://localhost:5173/#lifecycle");
+  });
+
+  test("should not destroy anything on the initial render", async ({
+    page,
+  }) => {
+    await expect(page.getByTestId("tree")).toBeVisible();
+    await expect(page.getByTestId("leaf-c")).toBeVisible();
+    expect(await destroyedLog(page)).toEqual([]);
+  });
+
+  test("should destroy a removed row and its component once", async ({
+    page,
+  }) => {
+    await page.getByTestId("remove-row-btn").click();
+    await expect(page.getByTestId("row-c")).toHaveCount(0);
+    expect(await destroyedLog(page)).toEqual(["leaf-c", "row-c"]);
+  });
+
+  test("should destroy the previous branch of if and switch", async ({
+    page,
+  }) => {
+    await page.getByTestId("toggle-details-btn").click();
+    expect(await destroyedLog(page)).toEqual(["details"]);
+
+    await page.getByTestId("switch-mode-btn").click();
+    await expect(page.getByTestId("editing")).toBeVisible();
+    expect(await destroyedLog(page)).toEqual(["details", "viewing"]);
+
+    await page.getByTestId("switch-mode-btn").click();
+    await expect(page.getByTestId("viewing")).toBeVisible();
+    expect(await destroyedLog(page)).toEqual(["details", "editing", "viewing"]);
+  });
+
+  test("should destroy every element once when the root is torn down", async ({
+    page,
+  }) => {
+    await page.getByTestId("teardown-btn").click();
+    await expect(page.getByTestId("tree")).toHaveCount(0);
+    expect(await destroyedLog(page)).toEqual([
+      "details",
+      "leaf-a",
+      "leaf-b",
+      "leaf-c",
+      "row-a",
+      "row-b",
+      "row-c",
+      "tree",
+      "viewing",
+    ]);
+  });
+
+  test("should not destroy removed views again on teardown", async ({
+    page,
+  }) => {
+    await page.getByTestId("remove-row-btn").click();
+    await page.getByTestId("switch-mode-btn").click();
+    await page.getByTestId("teardown-btn").click();
+    expect(await destroyedLog(page)).toEqual([
+      "details",
+      "editing",
+      "leaf-a",
+      "leaf-b",
+      "leaf-c",
+      "row-a",
+      "row-b",
+      "row-c",
+      "tree",
+      "viewing",
+    ]);
+  });
+});
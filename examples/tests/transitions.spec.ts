@@ -0,0 +1,73 @@
+import { test, expect, type Page } from "@playwright/test";
+
+test.describe("Transitions App", () => {
+  // The test ids logged by the example, once per call
+  const log = async (page: Page, testId: string) => {
+    const text = await page.getByTestId(testId).textContent();
+    return (text ?? "").split(" ").filter(Boolean).sort();
+  };
+
+  test.beforeEach(async ({ page }) => {
+    await page.goto("http://localhost:5173/#transitions");
+  });
+
+  test("should not call enter for the initial render", async ({ page }) => {
+    await expect(page.getByTestId("row-3")).toBeVisible();
+    await expect(page.getByTestId("panel")).toBeVisible();
+    expect(await log(page, "entered-log")).toEqual([]);
+  });
+
+  test("should call enter for an added row", async ({ page }) => {
+    await page.getByTestId("add-row-btn").click();
+    await expect(page.getByTestId("row-4")).toBeVisible();
+    expect(await log(page, "entered-log")).toEqual(["row-4"]);
+  });
+
+  test("should keep a removed row until its leave is done", async ({
+    page,
+  }) => {
+    await page.getByTestId("remove-row-btn").click();
+    await expect(page.getByTestId("pending-leaves")).toHaveText("1");
+    await expect(page.getByTestId("row-3")).toBeVisible();
+    expect(await log(page, "destroyed-log")).toEqual(["row-3"]);
+
+    await page.getByTestId("finish-leaves-btn").click();
+    await expect(page.getByTestId("pending-leaves")).toHaveText("0");
+    await expect(page.getByTestId("row-3")).toHaveCount(0);
+    expect(await log(page, "destroyed-log")).toEqual(["row-3"]);
+  });
+
+  test("should destroy each removed row exactly once", async ({ page }) => {
+    await page.getByTestId("remove-row-btn").click();
+    await page.getByTestId("remove-row-btn").click();
+    await expect(page.getByTestId("pending-leaves")).toHaveText("2");
+    await expect(page.getByTestId("row-2")).toBeVisible();
+    await expect(page.getByTestId("row-3")).toBeVisible();
+
+    await page.getByTestId("finish-leaves-btn").click();
+    await expect(page.getByTestId("row-2")).toHaveCount(0);
+    await expect(page.getByTestId("row-3")).toHaveCount(0);
+    await expect(page.getByTestId("row-1")).toBeVisible();
+    expect(await log(page, "destroyed-log")).toEqual(["row-2", "row-3"]);
+  });
+
+  test("should keep the previous if branch until its leave is done", async ({
+    page,
+  }) => {
+    await page.getByTestId("toggle-panel-btn").click();
+    await expect(page.getByTestId("no-panel")).toBeVisible();
+    await expect(page.getByTestId("panel")).toBeVisible();
+    expect(await log(page, "entered-log")).toEqual(["no-panel"]);
+    expect(await log(page, "destroyed-log")).toEqual(["panel"]);
+
+    await page.getByTestId("finish-leaves-btn").click();
+    await expect(page.getByTestId("panel")).toHaveCount(0);
+    expect(await log(page, "destroyed-log")).toEqual(["panel"]);
+
+    await page.getByTestId("toggle-panel-btn").click();
+    await page.getByTestId("finish-leaves-btn").click();
+    await expect(page.getByTestId("no-panel")).toHaveCount(0);
+    await expect(page.getByTestId("panel")).toHaveCount(1);
+    expect(await log(page, "destroyed-log")).toEqual(["no-panel", "panel"]);
+  });
+});
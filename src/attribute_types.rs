@@ -8,6 +8,36 @@ static ATTRIBUTE_TYPES: Lazy<HashMap<String, HashMap<String, String>>> = Lazy::n
     serde_json::from_str(s).expect("Failed to parse attribute_types.json")
 });
 
+/// Tag used to type `<dynamic>` elements, whose real tag is only known at runtime.
+pub const DYNAMIC_TAG: &str = "dynamic";
+
+/// Attributes shared by every known tag with the same type, used for `<dynamic>` elements.
+static COMMON_ATTRIBUTE_TYPES: Lazy<HashMap<String, String>> = Lazy::new(|| {
+    let mut tags = ATTRIBUTE_TYPES.values();
+    let Some(first) = tags.next() else {
+        return HashMap::new();
+    };
+    let mut common = first.clone();
+    for attrs in tags {
+        common.retain(|attr, ty| attrs.get(attr) == Some(ty));
+    }
+    common
+});
+
+/// All tags with known attribute types.
+pub fn known_tags() -> impl Iterator<Item = &'static String> {
+    ATTRIBUTE_TYPES.keys()
+}
+
+/// The TypeScript type of an element with the given tag.
+pub fn element_type(tag: &str) -> String {
+    if tag == DYNAMIC_TAG {
+        "HTMLElement".to_string()
+    } else {
+        format!("HTMLElementTagNameMap[\"{}\"]", tag)
+    }
+}
+
 /// Return the attribute type string for a given tag and attribute if known.
 ///
 /// Lookup strategy:
@@ -16,7 +46,11 @@ static ATTRIBUTE_TYPES: Lazy<HashMap<String, HashMap<String, String>>> = Lazy::n
 /// - If not found, try lowercased `attr`.
 pub fn attribute_type(tag: &str, attr: &str) -> Option<String> {
     let tag_key = tag.to_ascii_lowercase();
-    let attrs = ATTRIBUTE_TYPES.get(&tag_key)?;
+    let attrs = if tag_key == DYNAMIC_TAG {
+        &*COMMON_ATTRIBUTE_TYPES
+    } else {
+        ATTRIBUTE_TYPES.get(&tag_key)?
+    };
     // try exact attr first (preserve case if callers passed exact)
     if let Some(ty) = attrs.get(attr) {
         return Some(ty.clone());
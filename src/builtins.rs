@@ -6,6 +6,8 @@ use once_cell::sync::Lazy;
 pub enum BuiltinType {
     Prim(String),
     Var(u32),
+    /// An object with string keys, e.g. `Record<string, T>`
+    Dict(Box<BuiltinType>),
 }
 
 type Builtin = (Vec<BuiltinType>, BuiltinType);
@@ -23,7 +25,11 @@ pub static BUILTINS: Lazy<HashMap<String, Builtin>> = Lazy::new(|| {
 
     s.insert("numberToString".to_string(), (vec![num], str.clone()));
     s.insert("boolean".to_string(), (vec![bool, v(0), v(0)], v(0)));
-    s.insert("lookup".to_string(), (vec![v(0), str.clone(), v(1)], v(1)));
+    let dict = |value: BuiltinType| BuiltinType::Dict(Box::new(value));
+    s.insert(
+        "lookup".to_string(),
+        (vec![dict(v(0)), str.clone(), v(0)], v(0)),
+    );
 
     s
 });
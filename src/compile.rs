@@ -1,29 +1,51 @@
-use crate::emit::emit_views;
+use crate::attribute_types::{attribute_type, element_type, known_tags, DYNAMIC_TAG};
+use crate::emit::{emit_views, view_input_type_name, view_refs_type_name};
 use crate::error::Error;
 use crate::ir::{
-    CompileContext, CompiledView, ForLoopInfo, IfInfo, JsExpr, JsUpdater, SwitchInfo, UpdateKind,
-    ViewDefinition,
+    AwaitBranch, AwaitInfo, BindingInfo, CompileContext, CompiledView, DynamicInfo, ElementTag,
+    ForLoopInfo, HookInfo, IfInfo, JsExpr, JsUpdater, PortalInfo, ProvideInfo, RawHtmlInfo,
+    RefInfo, SwitchInfo, UpdateKind, ViewDefinition,
 };
 use crate::lang::{
     collect_attr_dependencies, expect_element, expr_dependencies, find_binding_attr,
     find_literal_attr, find_unique_child_by_name, has_bindings, infer_attr_type,
     split_data_attribute, validate_all_children_are_elements, validate_child_element_names,
     validate_single_child, AttrValue, Expr, Node, Span, SpannedAttribute, StringTemplateSegment,
+    TypeExpr,
 };
-use crate::template::ViewStub;
-use crate::ts_type::{env_to_ts_type, TsType};
+use crate::template::{ViewParam, ViewStub};
+use crate::ts_type::{env_to_generic_ts_type, is_fully_typed, type_to_ts_type, TsType};
 use crate::type_system::environment::{Env, InferContext, TypeMap};
 use crate::type_system::infer::infer;
-use crate::type_system::solver::solve;
-use crate::type_system::types::{Constraint, Expected};
+use crate::type_system::solver::{canonical_type, record_fields, solve, ExplainedError};
+use crate::type_system::types::{Constraint, Expected, Literal, Origin};
 use crate::type_system::Type;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Input field holding the `ViewState`s a caller passes for a view's slots
+const SLOTS_FIELD: &str = "slots";
+const DEFAULT_SLOT: &str = "default";
+/// Input field holding the values made available by enclosing `<provide>` forms
+const CONTEXT_FIELD: &str = "context";
 
 struct TypeEnv {
     env: Env,
     infer_ctx: InferContext,
     constraints: Vec<Constraint>,
     views: HashMap<String, TypeMap>,
+    // Slots declared by the view being compiled, and by each compiled view
+    slots: BTreeMap<String, Span>,
+    view_slots: HashMap<String, BTreeMap<String, Span>>,
+    // Inputs of each compiled view that callers may omit
+    view_optional: HashMap<String, HashSet<String>>,
+    // Type parameters of the view being declared or solved
+    generics: Vec<(String, Type)>,
+    // Variables the view's parameters declare as `any`
+    declared_any: Vec<Type>,
+    // Views whose errors have been reported; calls to them are not checked
+    failed_views: HashSet<String>,
+    loop_depth: usize,
 }
 
 impl TypeEnv {
@@ -33,6 +55,13 @@ impl TypeEnv {
             infer_ctx: InferContext::new(),
             constraints: Vec::new(),
             views: HashMap::new(),
+            slots: BTreeMap::new(),
+            view_slots: HashMap::new(),
+            view_optional: HashMap::new(),
+            generics: Vec::new(),
+            declared_any: Vec::new(),
+            failed_views: HashSet::new(),
+            loop_depth: 0,
         }
     }
 
@@ -46,16 +75,482 @@ impl TypeEnv {
         )
     }
 
-    fn solve_view(&mut self, view_name: String) -> Result<TsType, Error> {
-        solve(&mut self.infer_ctx, &self.constraints).map_err(|e| e.to_error())?;
-        let ts_type = env_to_ts_type(&self.env);
-        self.views.insert(view_name, self.env.globals().clone());
+    fn solve_view(
+        &mut self,
+        view_stub: &ViewStub,
+        strict: bool,
+    ) -> Result<(Vec<String>, TsType), Vec<Error>> {
+        self.declare_generics(view_stub);
+        self.constrain_params(view_stub)
+            .map_err(|error| vec![error])?;
+        solve(&mut self.infer_ctx, &self.constraints)
+            .map_err(|errors| errors.iter().map(ExplainedError::to_error).collect_vec())?;
+        if view_stub.strict.unwrap_or(strict) {
+            self.check_strict()?;
+        }
+        self.view_input_type(view_stub).map_err(|error| vec![error])
+    }
+
+    /// In strict mode every input's type must be fully known, so that no `any` or partially
+    /// inferred object reaches the generated input type. Context values are typed by the
+    /// views that provide them and are not checked.
+    fn check_strict(&self) -> Result<(), Vec<Error>> {
+        let allowed: HashSet<usize> = self
+            .generics
+            .iter()
+            .map(|(_, ty)| ty)
+            .chain(&self.declared_any)
+            .filter_map(|ty| match canonical_type(ty) {
+                Type::Var(point) => Some(point.id()),
+                _ => None,
+            })
+            .collect();
+        let errors = self
+            .env
+            .globals()
+            .iter()
+            .filter(|(name, _)| *name != CONTEXT_FIELD)
+            .map(|(name, ty)| (name, canonical_type(ty)))
+            .filter(|(_, ty)| !is_fully_typed(ty, &allowed))
+            .filter_map(|(name, ty)| Some((name, ty, *self.env.global_span(name)?)))
+            .sorted_by_key(|(_, _, span)| span.start)
+            .map(|(name, ty, span)| Error {
+                message: format!("The type of '{}' is not fully known", name),
+                main_span: span,
+                labels: vec![(
+                    span,
+                    format!(
+                        "Inferred as {}; declare its type with <param name=\"{}\" type='...'/>",
+                        type_to_ts_type(&ty),
+                        name
+                    ),
+                )],
+            })
+            .collect_vec();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn constrain_params(&mut self, view_stub: &ViewStub) -> Result<(), Error> {
+        let view_name = &view_stub.name;
+        let params = &view_stub.params;
+        if !params.is_empty() {
+            let imported = imported_type_names(view_stub);
+            self.check_undeclared_inputs(view_name, params)?;
+            // Solve declarations first so that mismatches are reported where the input is used
+            let mut constraints = Vec::new();
+            for param in params {
+                let ty = self
+                    .env
+                    .get(&mut self.infer_ctx, &param.name, &param.name_span);
+                if let Some(type_expr) = &param.ty {
+                    let declared = self.declared_type(type_expr, &imported)?;
+                    constraints.push(Constraint::Equal(
+                        param.span,
+                        ty.clone(),
+                        declared,
+                        Origin::Declaration(param.name.clone()),
+                    ));
+                }
+                if let Some(default) = &param.default {
+                    if let Some(default_type) = default_type(param)? {
+                        constraints.push(Constraint::Equal(
+                            *default.span(),
+                            ty,
+                            default_type,
+                            Origin::Declaration(param.name.clone()),
+                        ));
+                    }
+                }
+            }
+            constraints.append(&mut self.constraints);
+            self.constraints = constraints;
+        }
+        Ok(())
+    }
+
+    fn view_input_type(&mut self, view_stub: &ViewStub) -> Result<(Vec<String>, TsType), Error> {
+        let view_name = view_stub.name.clone();
+        let params = &view_stub.params;
+        self.check_generics(view_stub)?;
+        let (generics, mut ts_type) = env_to_generic_ts_type(&self.env, &self.generics);
+        check_aliased_params(params, &ts_type)?;
+        let optional: HashSet<String> = params
+            .iter()
+            .filter(|param| param.default.is_some())
+            .map(|param| param.name.clone())
+            .collect();
+        if let TsType::Object(fields) = &mut ts_type {
+            for name in &optional {
+                if let Some(field) = fields.remove(name) {
+                    fields.insert(name.clone(), TsType::Optional(Box::new(field)));
+                }
+            }
+        }
+        // Views that neither read nor pass on any context don't need one
+        if let TsType::Object(fields) = &mut ts_type {
+            if matches!(fields.get(CONTEXT_FIELD), Some(TsType::Object(context)) if context.is_empty())
+            {
+                fields.remove(CONTEXT_FIELD);
+            }
+            // Callers only pass on the values a view reads from its context, so it can't be
+            // used as a whole
+            if let Some(context) = fields.get(CONTEXT_FIELD) {
+                if !matches!(context, TsType::Object(_)) {
+                    let span = self
+                        .env
+                        .global_span(&CONTEXT_FIELD.to_string())
+                        .copied()
+                        .unwrap_or(view_stub.name_span);
+                    return Err(Error {
+                        message: format!(
+                            "'{}' is reserved for values from <provide>",
+                            CONTEXT_FIELD
+                        ),
+                        main_span: span,
+                        labels: vec![(
+                            span,
+                            format!(
+                                "Used as {} here; read a provided value such as '{}.theme' instead",
+                                context, CONTEXT_FIELD
+                            ),
+                        )],
+                    });
+                }
+            }
+            // Calls within a cycle were checked against the declared parameters alone
+            if view_stub.recursive && fields.contains_key(CONTEXT_FIELD) {
+                return Err(Error {
+                    message: format!("Recursive view '{}' cannot use context", view_name),
+                    main_span: view_stub.name_span,
+                    labels: vec![(
+                        view_stub.name_span,
+                        "Pass the values it needs as parameters instead".to_string(),
+                    )],
+                });
+            }
+        }
+        let slots = std::mem::take(&mut self.slots);
+        if let Some(slot_span) = slots.values().next() {
+            if self.env.globals().contains_key(SLOTS_FIELD) {
+                return Err(Error {
+                    message: format!(
+                        "'{}' is reserved for the children of views with a <slot>",
+                        SLOTS_FIELD
+                    ),
+                    main_span: *slot_span,
+                    labels: vec![(*slot_span, "Slot declared here".to_string())],
+                });
+            }
+            if let TsType::Object(fields) = &mut ts_type {
+                let slot_fields = slots
+                    .keys()
+                    .map(|name| {
+                        (
+                            name.clone(),
+                            TsType::SimpleType("ViewState<any>".to_string()),
+                        )
+                    })
+                    .collect();
+                fields.insert(SLOTS_FIELD.to_string(), TsType::Object(slot_fields));
+            }
+        }
+        self.views
+            .insert(view_name.clone(), self.env.globals().clone());
+        self.view_slots.insert(view_name.clone(), slots);
+        self.view_optional.insert(view_name, optional);
         self.env = Env::new();
         self.constraints = Vec::new();
-        Ok(ts_type)
+        self.generics = Vec::new();
+        self.declared_any = Vec::new();
+        Ok((generics, ts_type))
+    }
+
+    /// Give up on a view after an error, discarding its partial state.
+    fn skip_view(&mut self, view_stub: &ViewStub) {
+        self.failed_views.insert(view_stub.name.clone());
+        self.views.remove(&view_stub.name);
+        self.env = Env::new();
+        self.constraints = Vec::new();
+        self.slots = BTreeMap::new();
+        self.generics = Vec::new();
+        self.declared_any = Vec::new();
+        self.loop_depth = 0;
+    }
+
+    fn declare_generics(&mut self, view_stub: &ViewStub) {
+        self.generics = view_stub
+            .generics
+            .iter()
+            .map(|(name, _)| (name.clone(), Type::Var(self.infer_ctx.fresh_named(name))))
+            .collect();
+    }
+
+    /// Declared type parameters must stay unconstrained, and distinct from one another.
+    fn check_generics(&self, view_stub: &ViewStub) -> Result<(), Error> {
+        let mut seen: Vec<(usize, &str)> = Vec::new();
+        for ((name, ty), (_, span)) in self.generics.iter().zip(&view_stub.generics) {
+            match canonical_type(ty) {
+                Type::Var(point) => {
+                    if let Some((_, other)) = seen.iter().find(|(id, _)| *id == point.id()) {
+                        return Err(Error {
+                            message: format!(
+                                "Type parameters '{}' and '{}' of view '{}' must be distinct",
+                                other, name, view_stub.name
+                            ),
+                            main_span: *span,
+                            labels: vec![(
+                                *span,
+                                format!("The view uses '{}' where it expects '{}'", name, other),
+                            )],
+                        });
+                    }
+                    seen.push((point.id(), name));
+                }
+                bound => {
+                    return Err(Error {
+                        message: format!(
+                            "Type parameter '{}' of view '{}' must stay generic",
+                            name, view_stub.name
+                        ),
+                        main_span: *span,
+                        labels: vec![(
+                            *span,
+                            format!("The view uses it as {}", type_to_ts_type(&bound)),
+                        )],
+                    });
+                }
+            }
+        }
+        Ok(())
     }
 }
 
+impl TypeEnv {
+    /// Register a recursive view's declared parameters before it is compiled, so that calls
+    /// within its cycle can be checked against them.
+    fn declare_recursive_view(&mut self, view_stub: &ViewStub) -> Result<(), Error> {
+        let view_name = &view_stub.name;
+        if view_stub.params.is_empty() {
+            return Err(Error {
+                message: format!(
+                    "Recursive view '{}' must declare its parameters with <param>",
+                    view_name
+                ),
+                main_span: view_stub.name_span,
+                labels: vec![(
+                    view_stub.name_span,
+                    "This view calls itself, directly or through other views".to_string(),
+                )],
+            });
+        }
+
+        let imported = imported_type_names(view_stub);
+        self.declare_generics(view_stub);
+        let mut param_types = TypeMap::new();
+        for param in &view_stub.params {
+            let Some(type_expr) = &param.ty else {
+                return Err(Error {
+                    message: format!(
+                        "Parameter '{}' of recursive view '{}' needs a type",
+                        param.name, view_name
+                    ),
+                    main_span: param.span,
+                    labels: vec![(param.span, "Add a 'type' attribute".to_string())],
+                });
+            };
+            let declared = self.declared_type(type_expr, &imported)?;
+            param_types.insert(param.name.clone(), declared);
+        }
+
+        let optional = view_stub
+            .params
+            .iter()
+            .filter(|param| param.default.is_some())
+            .map(|param| param.name.clone())
+            .collect();
+        let mut slots = BTreeMap::new();
+        collect_slot_names(&view_stub.root, &mut slots);
+
+        self.views.insert(view_name.clone(), param_types);
+        self.view_optional.insert(view_name.clone(), optional);
+        self.view_slots.insert(view_name.clone(), slots);
+        self.generics = Vec::new();
+        self.declared_any = Vec::new();
+        Ok(())
+    }
+
+    fn check_undeclared_inputs(&self, view_name: &str, params: &[ViewParam]) -> Result<(), Error> {
+        let undeclared = self
+            .env
+            .globals()
+            .keys()
+            .filter(|name| {
+                *name != CONTEXT_FIELD && !params.iter().any(|param| &param.name == *name)
+            })
+            .filter_map(|name| self.env.global_span(name).map(|span| (name, *span)))
+            .min_by_key(|(_, span)| span.start);
+
+        match undeclared {
+            Some((name, span)) => Err(Error {
+                message: format!(
+                    "'{}' is not a declared parameter of view '{}'",
+                    name, view_name
+                ),
+                main_span: span,
+                labels: vec![(
+                    span,
+                    format!("Declare it with <param name=\"{}\"/> or fix the name", name),
+                )],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Convert a declared parameter type; object types are closed, so using a field
+    /// the declaration does not list is an error.
+    fn declared_type(
+        &mut self,
+        type_expr: &TypeExpr,
+        imported: &HashSet<&str>,
+    ) -> Result<Type, Error> {
+        if let TypeExpr::Named(name, _) = type_expr {
+            if let Some((_, ty)) = self.generics.iter().find(|(generic, _)| generic == name) {
+                return Ok(ty.clone());
+            }
+        }
+        match type_expr {
+            TypeExpr::Named(name, span) => match name.as_str() {
+                "string" | "number" | "boolean" | "void" => Ok(Type::Prim(name.clone())),
+                "true" | "false" => Ok(Type::Literal(literal_of(type_expr).into_iter().collect())),
+                "any" => {
+                    let ty = Type::Var(self.infer_ctx.fresh_point());
+                    self.declared_any.push(ty.clone());
+                    Ok(ty)
+                }
+                _ if imported.contains(name.as_str()) => Ok(Type::Named(name.clone())),
+                _ => Err(Error {
+                    message: format!("Unknown type '{}'", name),
+                    main_span: *span,
+                    labels: vec![(
+                        *span,
+                        "Expected string, number, boolean, void, any, a type parameter of the view, or a type named in <import-type>"
+                            .to_string(),
+                    )],
+                }),
+            },
+            TypeExpr::StringLiteral(..) | TypeExpr::NumberLiteral(..) => {
+                Ok(Type::Literal(literal_of(type_expr).into_iter().collect()))
+            }
+            TypeExpr::Array(elem) => Ok(Type::Array(Box::new(self.declared_type(elem, imported)?))),
+            TypeExpr::Record(value) => Ok(Type::Dict(Box::new(self.declared_type(value, imported)?))),
+            TypeExpr::Tuple(elems) => Ok(Type::Tuple(
+                elems
+                    .iter()
+                    .map(|elem| self.declared_type(elem, imported))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            )),
+            TypeExpr::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, ty)| Ok((name.clone(), self.declared_type(ty, imported)?)))
+                    .collect::<Result<BTreeMap<_, _>, Error>>()?;
+                let closed = self.infer_ctx.fresh_empty_row();
+                if fields.is_empty() {
+                    Ok(Type::Record(closed))
+                } else {
+                    Ok(Type::Record(
+                        self.infer_ctx.fresh_row_extend(fields, closed),
+                    ))
+                }
+            }
+            TypeExpr::Function(args, ret) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.declared_type(arg, imported))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(Type::Fun(args, Box::new(self.declared_type(ret, imported)?)))
+            }
+            TypeExpr::Union(members, span) => {
+                let literals = members.iter().map(literal_of).collect::<Option<BTreeSet<_>>>();
+                match literals {
+                    Some(literals) if literals.iter().map(Literal::primitive).all_equal() => {
+                        Ok(Type::Literal(literals))
+                    }
+                    _ => Err(Error {
+                        message: "Only unions of literals of one primitive type are supported".to_string(),
+                        main_span: *span,
+                        labels: vec![(*span, "Union declared here".to_string())],
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// The literal a type expression stands for, if it is one
+fn literal_of(type_expr: &TypeExpr) -> Option<Literal> {
+    match type_expr {
+        TypeExpr::StringLiteral(value, _) => Some(Literal::String(value.clone())),
+        TypeExpr::NumberLiteral(value, _) => Some(Literal::Number(value.clone())),
+        TypeExpr::Named(name, _) if name == "true" || name == "false" => {
+            Some(Literal::Boolean(name == "true"))
+        }
+        _ => None,
+    }
+}
+
+/// The type a parameter's default implies, or `None` when the declared type is a literal
+/// union, in which case the default is checked to be one of its members.
+fn default_type(param: &ViewParam) -> Result<Option<Type>, Error> {
+    let Some(default) = &param.default else {
+        return Ok(None);
+    };
+    let literal = match default {
+        Expr::StringTemplate(segments, _) => Literal::String(
+            segments
+                .iter()
+                .map(|segment| match segment {
+                    StringTemplateSegment::Literal(text) => text.as_str(),
+                    StringTemplateSegment::Interpolation(_) => "",
+                })
+                .collect::<String>(),
+        ),
+        Expr::Number(value, _) => Literal::Number(value.clone()),
+        _ => return Ok(None),
+    };
+    let members: Option<Vec<Literal>> = match &param.ty {
+        Some(TypeExpr::Union(members, _)) => Some(members.iter().filter_map(literal_of).collect()),
+        Some(type_expr) => literal_of(type_expr).map(|literal| vec![literal]),
+        None => None,
+    };
+
+    match members {
+        Some(members) if members.contains(&literal) => Ok(None),
+        Some(members) => {
+            let span = *default.span();
+            Err(Error {
+                message: format!(
+                    "Default for '{}' must be one of: {}",
+                    param.name,
+                    members.iter().join(", ")
+                ),
+                main_span: span,
+                labels: vec![(span, "Default is not in the declared type".to_string())],
+            })
+        }
+        None => Ok(Some(Type::Prim(literal.primitive().to_string()))),
+    }
+}
+
+/// A union of string literals, e.g. `"a" | "b"`
+fn string_literal_union<'a>(values: impl Iterator<Item = &'a String>) -> Type {
+    Type::Literal(values.map(|value| Literal::String(value.clone())).collect())
+}
+
 #[derive(Debug, Clone)]
 pub struct ViewTypeInfo {
     pub name: String,
@@ -69,35 +564,277 @@ pub struct CompileOutput {
     pub view_types: Vec<ViewTypeInfo>,
 }
 
-pub fn compile_views(sorted_view_stubs: &[ViewStub]) -> Result<CompileOutput, Error> {
+/// Settings that apply to every view of a compilation
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Reject views whose input types are not fully known, unless a view sets `strict` itself
+    pub strict: bool,
+    /// Emit readonly arrays and fields in input types, unless a view sets `readonly` itself
+    pub readonly: bool,
+}
+
+/// Compile every view, reporting all of their errors. A view that fails is skipped, and calls
+/// to it accept any attributes so that its callers are still checked.
+pub fn compile_views(
+    sorted_view_stubs: &[ViewStub],
+    options: &CompileOptions,
+) -> Result<CompileOutput, Vec<Error>> {
     let mut env = TypeEnv::new();
     let mut compiled_views = Vec::new();
     let mut view_types = Vec::new();
+    let mut errors = Vec::new();
 
-    // assumes sorted_view_stubs are in dependency order
+    for view_stub in sorted_view_stubs
+        .iter()
+        .filter(|view_stub| view_stub.recursive)
+    {
+        if let Err(error) = env.declare_recursive_view(view_stub) {
+            errors.push(error);
+            env.skip_view(view_stub);
+        }
+    }
+
+    // assumes sorted_view_stubs are in dependency order, apart from calls within a cycle
     for view_stub in sorted_view_stubs {
-        let view_name = view_stub.name.clone();
-        let mut context = CompileContext::new();
-        let root = compile_view(&view_stub.root, &mut context, &mut env, view_stub.view_span)?;
-        let ts_type = env.solve_view(view_name.clone())?;
-
-        view_types.push(ViewTypeInfo {
-            name: view_name.clone(),
-            name_span: view_stub.name_span,
-            input_type: ts_type.clone(),
-        });
+        if env.failed_views.contains(&view_stub.name) {
+            continue;
+        }
+        match compile_view_stub(view_stub, options, &mut env) {
+            Ok(view_def) => {
+                view_types.push(ViewTypeInfo {
+                    name: view_def.view_name.clone(),
+                    name_span: view_stub.name_span,
+                    input_type: view_def.ts_type.clone(),
+                });
+                compiled_views.push(view_def);
+            }
+            Err(mut view_errors) => {
+                errors.append(&mut view_errors);
+                env.skip_view(view_stub);
+            }
+        }
+    }
 
-        compiled_views.push(ViewDefinition {
-            view_name: view_name.clone(),
-            root,
-            context,
-            ts_type,
-        });
+    errors.append(&mut check_alias_names(sorted_view_stubs));
+    let type_imports = collect_type_imports(sorted_view_stubs).map_err(|error| vec![error]);
+    match type_imports {
+        Ok(type_imports) if errors.is_empty() => {
+            let code = emit_views(&compiled_views, &type_imports);
+            Ok(CompileOutput { code, view_types })
+        }
+        Ok(_) => Err(errors),
+        Err(mut import_errors) => {
+            errors.append(&mut import_errors);
+            Err(errors)
+        }
+    }
+}
+
+fn compile_view_stub(
+    view_stub: &ViewStub,
+    options: &CompileOptions,
+    env: &mut TypeEnv,
+) -> Result<ViewDefinition, Vec<Error>> {
+    let mut context = CompileContext::new();
+    context.input_defaults = view_stub
+        .params
+        .iter()
+        .filter_map(|param| Some((param.name.clone(), param.default.clone()?)))
+        .collect();
+    let root = compile_view(&view_stub.root, &mut context, env, view_stub.view_span)
+        .and_then(|root| check_nested_refs(&context).map(|()| root))
+        .map_err(|error| vec![error])?;
+    let (generics, ts_type) = env.solve_view(view_stub, options.strict)?;
+    let aliases = view_stub
+        .params
+        .iter()
+        .filter_map(|param| Some((param.name.clone(), param.alias.as_ref()?.0.clone())))
+        .collect();
+    let inferred_generics = generics
+        .iter()
+        .filter(|name| {
+            !view_stub
+                .generics
+                .iter()
+                .any(|(declared, _)| declared == *name)
+        })
+        .cloned()
+        .collect();
+
+    Ok(ViewDefinition {
+        view_name: view_stub.name.clone(),
+        root,
+        context,
+        generics,
+        inferred_generics,
+        ts_type,
+        aliases,
+        readonly: view_stub.readonly.unwrap_or(options.readonly),
+    })
+}
+
+/// An `alias` names the input's object type, or the objects in its arrays, promises and
+/// dictionaries, so the input must have one.
+fn check_aliased_params(params: &[ViewParam], ts_type: &TsType) -> Result<(), Error> {
+    fn has_object(ty: &TsType) -> bool {
+        match ty {
+            TsType::Object(fields) => !fields.is_empty(),
+            TsType::Array(inner)
+            | TsType::Promise(inner)
+            | TsType::Record(inner)
+            | TsType::Optional(inner) => has_object(inner),
+            _ => false,
+        }
+    }
+
+    let TsType::Object(fields) = ts_type else {
+        return Ok(());
+    };
+    for param in params {
+        let (Some((alias, span)), Some(ty)) = (&param.alias, fields.get(&param.name)) else {
+            continue;
+        };
+        if !has_object(ty) {
+            return Err(Error {
+                message: format!(
+                    "Parameter '{}' has no object type to name '{}'",
+                    param.name, alias
+                ),
+                main_span: *span,
+                labels: vec![(*span, format!("'{}' has type {}", param.name, ty))],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Aliases share the generated file's scope with input, refs and imported types.
+fn check_alias_names(view_stubs: &[ViewStub]) -> Vec<Error> {
+    let mut used: HashSet<String> = view_stubs
+        .iter()
+        .flat_map(|view| {
+            [
+                view_input_type_name(&view.name),
+                view_refs_type_name(&view.name),
+            ]
+        })
+        .chain(
+            view_stubs
+                .iter()
+                .flat_map(|view| &view.type_imports)
+                .flat_map(|import| import.names.iter().map(|(name, _)| name.clone())),
+        )
+        .collect();
+    let mut aliases: HashMap<&str, Span> = HashMap::new();
+    let mut errors = Vec::new();
+    for (alias, span) in view_stubs
+        .iter()
+        .flat_map(|view| &view.params)
+        .filter_map(|param| param.alias.as_ref())
+    {
+        if let Some(previous) = aliases.get(alias.as_str()) {
+            errors.push(Error {
+                message: format!("Type name '{}' is used by more than one alias", alias),
+                main_span: *span,
+                labels: vec![
+                    (*span, "Used here".to_string()),
+                    (*previous, "And here".to_string()),
+                ],
+            });
+        } else if used.contains(alias) {
+            errors.push(Error {
+                message: format!("Type name '{}' is already used", alias),
+                main_span: *span,
+                labels: vec![(
+                    *span,
+                    "Input, refs and imported types share the generated file's scope".to_string(),
+                )],
+            });
+        }
+        aliases.insert(alias, *span);
+        used.insert(alias.clone());
     }
+    errors
+}
 
-    let code = emit_views(&compiled_views);
+/// Names of the types imported by a view's template.
+fn imported_type_names(view_stub: &ViewStub) -> HashSet<&str> {
+    view_stub
+        .type_imports
+        .iter()
+        .flat_map(|import| import.names.iter().map(|(name, _)| name.as_str()))
+        .collect()
+}
 
-    Ok(CompileOutput { code, view_types })
+/// Find the slots a view declares without compiling it; malformed slots are reported when the
+/// view is compiled.
+fn collect_slot_names(node: &Node, slots: &mut BTreeMap<String, Span>) {
+    match node {
+        Node::Element {
+            name,
+            attrs,
+            children,
+            span,
+            ..
+        } => {
+            if name == "slot" {
+                let slot_name = match attrs.iter().find(|attr| attr.name == "name") {
+                    Some(attr) => match find_literal_attr(std::slice::from_ref(attr), "name", span)
+                    {
+                        Ok((slot_name, _)) => slot_name,
+                        Err(_) => return,
+                    },
+                    None => DEFAULT_SLOT.to_string(),
+                };
+                slots.entry(slot_name).or_insert(*span);
+            }
+            for child in children {
+                collect_slot_names(child, slots);
+            }
+        }
+        Node::ComponentCall { children, .. } => {
+            for child in children {
+                collect_slot_names(child, slots);
+            }
+        }
+        Node::Text { .. } | Node::Expr(_) => {}
+    }
+}
+
+/// Merge the `<import-type>`s of all templates into module -> names, rejecting a name
+/// imported from two different modules since the generated file has a single scope.
+fn collect_type_imports(
+    view_stubs: &[ViewStub],
+) -> Result<BTreeMap<String, BTreeSet<String>>, Error> {
+    let mut imports: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut sources: HashMap<&str, (&str, Span)> = HashMap::new();
+
+    for import in view_stubs.iter().flat_map(|view| &view.type_imports) {
+        for (name, span) in &import.names {
+            if let Some((from, previous_span)) = sources.get(name.as_str()) {
+                if *from != import.from {
+                    return Err(Error {
+                        message: format!(
+                            "Type '{}' is imported from both '{}' and '{}'",
+                            name, from, import.from
+                        ),
+                        main_span: *span,
+                        labels: vec![
+                            (*span, format!("Imported from '{}' here", import.from)),
+                            (*previous_span, format!("Imported from '{}' here", from)),
+                        ],
+                    });
+                }
+            }
+            sources.insert(name, (&import.from, *span));
+            imports
+                .entry(import.from.clone())
+                .or_default()
+                .insert(name.clone());
+        }
+    }
+
+    Ok(imports)
 }
 
 fn compile_view(
@@ -108,6 +845,18 @@ fn compile_view(
 ) -> Result<JsExpr, Error> {
     let expr = compile_node(node, context, env)?;
 
+    if matches!(expr, JsExpr::RawHtml(_)) {
+        return Err(Error {
+            message: "<raw> elements cannot be root elements; wrap them in a container."
+                .to_string(),
+            main_span: view_span,
+            labels: vec![
+                (view_span, "View".to_string()),
+                (*node.span(), "element".to_string()),
+            ],
+        });
+    }
+
     if matches!(expr, JsExpr::LoopElements(_)) {
         return Err(Error {
             message: "<for> elements cannot be root elements; wrap them in a container."
@@ -144,8 +893,20 @@ fn compile_node(
                 compile_switch(attrs, children, span, context, env)
             } else if name == "use" {
                 compile_use(attrs, span, context, env)
+            } else if name == "slot" {
+                compile_slot(attrs, children, span, env)
+            } else if name == "await" {
+                compile_await(attrs, children, span, context, env)
+            } else if name == "dynamic" {
+                compile_dynamic(attrs, children, span, context, env)
+            } else if name == "provide" {
+                compile_provide(attrs, children, span, context, env)
+            } else if name == "portal" {
+                compile_portal(attrs, children, span, context, env)
+            } else if name == "raw" {
+                compile_raw(attrs, children, span, context, env)
             } else {
-                compile_element(name, attrs, children, context, env)
+                compile_element(name, attrs, children, None, context, env)
             }
         }
         Node::ComponentCall {
@@ -169,7 +930,10 @@ fn compile_node(
                 },
             });
 
-            env.infer(expr, Expected::Expect(Type::Prim("string".to_string())));
+            env.infer(
+                expr,
+                Expected::Expect(Type::Prim("string".to_string()), Origin::Text),
+            );
 
             Ok(JsExpr::Ref(node_idx))
         }
@@ -180,6 +944,7 @@ fn compile_element(
     name: &str,
     attrs: &[SpannedAttribute],
     children: &[Node],
+    dynamic_tag: Option<Expr>,
     context: &mut CompileContext,
     env: &mut TypeEnv,
 ) -> Result<JsExpr, Error> {
@@ -192,8 +957,63 @@ fn compile_element(
         child_exprs.push(expr);
     }
     let node_idx = context.constructors.len();
+    let mut element_ref = None;
+    let mut mount_hooks = Vec::new();
+    let mut destroy_hooks = Vec::new();
+    let mut has_binding = false;
+    let mut directives = Vec::new();
     for attr in attrs {
         let k = &attr.name;
+        if let Some(kind) = compile_directive(attr, attrs, name, node_idx, env)? {
+            if has_bindings(&attr.value) {
+                prop_updaters.push(JsUpdater {
+                    dependencies: collect_attr_dependencies(&attr.value),
+                    kind: kind.clone(),
+                });
+            }
+            directives.push(kind);
+            continue;
+        }
+        if k == "ref" {
+            element_ref = Some(compile_ref(attr, name, node_idx, context)?);
+            continue;
+        }
+        if let Some(prop) = k.strip_prefix("bind:") {
+            let (binding, field) = compile_binding(attr, prop, name, node_idx, env)?;
+            let value = AttrValue::Expr(field);
+            prop_updaters.push(JsUpdater {
+                dependencies: collect_attr_dependencies(&value),
+                kind: UpdateKind::Prop {
+                    node_idx,
+                    prop: prop.to_string(),
+                    value: value.clone(),
+                },
+            });
+            props.push((prop.to_string(), value));
+            context.bindings.push(binding);
+            has_binding = true;
+            continue;
+        }
+        if k == "onmount" || k == "ondestroy" {
+            let handler = find_binding_attr(std::slice::from_ref(attr), k, &attr.span)?;
+            env.infer(
+                &handler,
+                Expected::Expect(
+                    Type::Fun(
+                        vec![Type::Prim(element_type(name))],
+                        Box::new(Type::Prim("void".to_string())),
+                    ),
+                    Origin::attribute(name, k),
+                ),
+            );
+            let hook = HookInfo { node_idx, handler };
+            if k == "onmount" {
+                mount_hooks.push(hook);
+            } else {
+                destroy_hooks.push(hook);
+            }
+            continue;
+        }
         let v = &attr.value;
 
         // Map attribute name to DOM property name where appropriate (e.g., class -> className)
@@ -219,7 +1039,10 @@ fn compile_element(
             AttrValue::Template(segments) => {
                 for seg in segments {
                     if let StringTemplateSegment::Interpolation(expr) = seg {
-                        env.infer(expr, Expected::Expect(Type::Prim("string".to_string())));
+                        env.infer(
+                            expr,
+                            Expected::Expect(Type::Prim("string".to_string()), Origin::Text),
+                        );
                     }
                 }
             }
@@ -229,7 +1052,10 @@ fn compile_element(
                 } else {
                     infer_attr_type(k, name)
                 };
-                env.infer(expr, Expected::Expect(Type::Prim(ty)));
+                env.infer(
+                    expr,
+                    Expected::Expect(Type::Prim(ty), Origin::attribute(name, k)),
+                );
             }
         }
 
@@ -259,12 +1085,30 @@ fn compile_element(
         }
     }
     let element_expr = JsExpr::Element {
-        tag: name.to_string(),
+        tag: match dynamic_tag {
+            Some(tag_expr) => ElementTag::Dynamic(tag_expr),
+            None => ElementTag::Static(name.to_string()),
+        },
         props,
         dataset,
         children: child_exprs,
     };
-    if !prop_updaters.is_empty() {
+    let has_hooks = !mount_hooks.is_empty() || !destroy_hooks.is_empty();
+    let has_directives = !directives.is_empty();
+    context.mount_hooks.extend(mount_hooks);
+    context.destroy_hooks.extend(destroy_hooks);
+    context.directives.extend(directives);
+    // Elements that are updated, exposed, passed to hooks or have directives applied get a
+    // constructor so they can be referenced
+    if !prop_updaters.is_empty()
+        || element_ref.is_some()
+        || has_hooks
+        || has_binding
+        || has_directives
+    {
+        if let Some((ref_name, ref_info)) = element_ref {
+            context.refs.insert(ref_name, ref_info);
+        }
         context.constructors.push(element_expr);
         context.updaters.extend(prop_updaters);
         Ok(JsExpr::Ref(node_idx))
@@ -274,6 +1118,270 @@ fn compile_element(
     }
 }
 
+/// Compile a `class:name={flag}` or `style:property={value}` directive, or return `None` for any
+/// other attribute.
+fn compile_directive(
+    attr: &SpannedAttribute,
+    attrs: &[SpannedAttribute],
+    tag: &str,
+    node_idx: usize,
+    env: &mut TypeEnv,
+) -> Result<Option<UpdateKind>, Error> {
+    let (prefix, name) = match attr.name.split_once(':') {
+        Some((prefix @ ("class" | "style"), name)) => (prefix, name),
+        _ => return Ok(None),
+    };
+    if name.is_empty() {
+        return Err(Error {
+            message: format!("Missing {} name after '{}:'", prefix, prefix),
+            main_span: attr.name_span,
+            labels: vec![(
+                attr.name_span,
+                format!(
+                    "e.g. {}",
+                    if prefix == "class" {
+                        "class:active"
+                    } else {
+                        "style:width"
+                    }
+                ),
+            )],
+        });
+    }
+
+    // A dynamic attribute rewrites the whole class or style, discarding directive changes
+    if let Some(whole) = attrs
+        .iter()
+        .find(|a| a.name == prefix && has_bindings(&a.value))
+    {
+        return Err(Error {
+            message: format!(
+                "'{}:' directives cannot be combined with a dynamic '{}' attribute",
+                prefix, prefix
+            ),
+            main_span: attr.name_span,
+            labels: vec![
+                (attr.name_span, "Directive used here".to_string()),
+                (
+                    whole.span,
+                    format!(
+                        "Make this '{}' static or move its bindings into directives",
+                        prefix
+                    ),
+                ),
+            ],
+        });
+    }
+
+    if prefix == "class" {
+        let value = find_binding_attr(std::slice::from_ref(attr), &attr.name, &attr.span)?;
+        env.infer(
+            &value,
+            Expected::Expect(
+                Type::Prim("boolean".to_string()),
+                Origin::attribute(tag, &attr.name),
+            ),
+        );
+        return Ok(Some(UpdateKind::ClassToggle {
+            node_idx,
+            class: name.to_string(),
+            value,
+        }));
+    }
+
+    match &attr.value {
+        AttrValue::Template(segments) => {
+            for seg in segments {
+                if let StringTemplateSegment::Interpolation(expr) = seg {
+                    env.infer(
+                        expr,
+                        Expected::Expect(Type::Prim("string".to_string()), Origin::Text),
+                    );
+                }
+            }
+        }
+        AttrValue::Expr(expr) => {
+            env.infer(
+                expr,
+                Expected::Expect(
+                    Type::Prim("string".to_string()),
+                    Origin::attribute(tag, &attr.name),
+                ),
+            );
+        }
+    }
+    Ok(Some(UpdateKind::Style {
+        node_idx,
+        property: name.to_string(),
+        value: attr.value.clone(),
+    }))
+}
+
+/// Compile `bind:prop={field}`, returning the binding and the bound field expression.
+fn compile_binding(
+    attr: &SpannedAttribute,
+    prop: &str,
+    tag: &str,
+    node_idx: usize,
+    env: &mut TypeEnv,
+) -> Result<(BindingInfo, Expr), Error> {
+    let event = match prop {
+        "value" => "input",
+        "checked" => "change",
+        _ => {
+            return Err(Error {
+                message: format!("Cannot bind '{}'", prop),
+                main_span: attr.name_span,
+                labels: vec![(
+                    attr.name_span,
+                    "Only bind:value and bind:checked are supported".to_string(),
+                )],
+            })
+        }
+    };
+
+    let (Some(prop_type), Some(_)) = (
+        attribute_type(tag, prop),
+        attribute_type(tag, &format!("on{}", event)),
+    ) else {
+        return Err(Error {
+            message: format!("<{}> has no bindable '{}' property", tag, prop),
+            main_span: attr.name_span,
+            labels: vec![(attr.name_span, format!("Not a property of <{}>", tag))],
+        });
+    };
+
+    let field = find_binding_attr(std::slice::from_ref(attr), &attr.name, &attr.span)?;
+    let field_name = match &field {
+        Expr::Variable(name, _) if !env.env.is_local(name) => name.clone(),
+        _ => {
+            return Err(Error {
+                message: format!("'{}' must be bound to an input field", attr.name),
+                main_span: *field.span(),
+                labels: vec![(
+                    *field.span(),
+                    format!("Use a top-level input, e.g. {}={{text}}", attr.name),
+                )],
+            })
+        }
+    };
+
+    let mut chars = field_name.chars();
+    let setter = match chars.next() {
+        Some(first) => format!("set{}{}", first.to_ascii_uppercase(), chars.as_str()),
+        None => unreachable!("identifiers are never empty"),
+    };
+
+    let value_type = Type::Prim(prop_type);
+    let origin = Origin::attribute(tag, &attr.name);
+    env.infer(&field, Expected::Expect(value_type.clone(), origin.clone()));
+    env.infer(
+        &Expr::Variable(setter.clone(), *field.span()),
+        Expected::Expect(
+            Type::Fun(vec![value_type], Box::new(Type::Prim("void".to_string()))),
+            origin,
+        ),
+    );
+
+    Ok((
+        BindingInfo {
+            node_idx,
+            prop: prop.to_string(),
+            event: event.to_string(),
+            setter,
+        },
+        field,
+    ))
+}
+
+fn compile_ref(
+    attr: &SpannedAttribute,
+    tag: &str,
+    node_idx: usize,
+    context: &CompileContext,
+) -> Result<(String, RefInfo), Error> {
+    let (ref_name, span) = find_literal_attr(std::slice::from_ref(attr), "ref", &attr.span)?;
+
+    let valid = ref_name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && ref_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(Error {
+            message: format!("'{}' is not a valid ref name", ref_name),
+            main_span: span,
+            labels: vec![(span, "Use a name like 'searchBox'".to_string())],
+        });
+    }
+
+    if let Some(existing) = context.refs.get(&ref_name) {
+        return Err(Error {
+            message: format!("Ref '{}' is used more than once in this view", ref_name),
+            main_span: span,
+            labels: vec![
+                (span, "Duplicate ref".to_string()),
+                (existing.span, "First used here".to_string()),
+            ],
+        });
+    }
+
+    Ok((
+        ref_name,
+        RefInfo {
+            node_idx,
+            tag: tag.to_string(),
+            span,
+        },
+    ))
+}
+
+/// Refs are only exposed for elements that always exist, so reject any inside
+/// loops, conditionals, switches or slot content.
+fn check_nested_refs(context: &CompileContext) -> Result<(), Error> {
+    for child in &context.child_views {
+        if let Some((name, ref_info)) = child.context.refs.iter().next() {
+            return Err(Error {
+                message: format!(
+                    "Ref '{}' is inside a <for>, <if>, <switch>, <await>, <portal>, <dynamic> or slot content",
+                    name
+                ),
+                main_span: ref_info.span,
+                labels: vec![(
+                    ref_info.span,
+                    "Refs must be on elements that always exist in the view".to_string(),
+                )],
+            });
+        }
+        check_nested_refs(&child.context)?;
+    }
+    Ok(())
+}
+
+/// Compile the optional `transition={hooks}` attribute of <for>, <if> and <switch>.
+fn compile_transition(
+    tag: &str,
+    attrs: &[SpannedAttribute],
+    env: &mut TypeEnv,
+) -> Result<Option<Expr>, Error> {
+    let Some(attr) = attrs.iter().find(|attr| attr.name == "transition") else {
+        return Ok(None);
+    };
+    let hooks = find_binding_attr(std::slice::from_ref(attr), "transition", &attr.span)?;
+    // `Transition` is the hooks type exported by the generated runtime, so it is kept opaque
+    // rather than modelled as a record of functions
+    env.infer(
+        &hooks,
+        Expected::Expect(
+            Type::Prim("Transition".to_string()),
+            Origin::attribute(tag, "transition"),
+        ),
+    );
+    Ok(Some(hooks))
+}
+
 fn compile_for_loop(
     attrs: &[SpannedAttribute],
     children: &[Node],
@@ -284,17 +1392,24 @@ fn compile_for_loop(
     let seq = find_binding_attr(attrs, "seq", span)?;
     let (var, _) = find_literal_attr(attrs, "as", span)?;
     validate_single_child(span, children)?;
+    let transition = compile_transition("for", attrs, env)?;
 
     let mut sub_context = CompileContext::new();
     let array_type = env.infer_ctx.fresh_point();
     let mut scope = HashMap::new();
     scope.insert(var.clone(), Type::Var(array_type.clone()));
     env.env.push_scope(scope);
-    let child_root = compile_view(&children[0], &mut sub_context, env, *span)?;
+    env.loop_depth += 1;
+    let child_root = compile_view(&children[0], &mut sub_context, env, *span);
+    env.loop_depth -= 1;
+    let child_root = child_root?;
     env.env.pop_scope();
     env.infer(
         &seq,
-        Expected::Expect(Type::Array(Box::new(Type::Var(array_type)))),
+        Expected::Expect(
+            Type::Array(Box::new(Type::Var(array_type))),
+            Origin::attribute("for", "seq"),
+        ),
     );
     let child_view_idx = context.child_views.len();
     context.child_views.push(CompiledView {
@@ -307,6 +1422,7 @@ fn compile_for_loop(
         child_view_idx,
         sequence_expr: seq.clone(),
         var_name: var.clone(),
+        transition,
     });
 
     // Return spread of loop elements
@@ -321,6 +1437,7 @@ fn compile_if(
     env: &mut TypeEnv,
 ) -> Result<JsExpr, Error> {
     let condition = find_binding_attr(attrs, "condition", span)?;
+    let transition = compile_transition("if", attrs, env)?;
 
     // Validate children are all elements with expected names
     validate_all_children_are_elements(span, children)?;
@@ -371,7 +1488,10 @@ fn compile_if(
 
     env.infer(
         &condition,
-        Expected::Expect(Type::Prim("boolean".to_string())),
+        Expected::Expect(
+            Type::Prim("boolean".to_string()),
+            Origin::attribute("if", "condition"),
+        ),
     );
 
     // Track if information
@@ -379,12 +1499,123 @@ fn compile_if(
         then_view_idx,
         else_view_idx,
         condition_expr: condition.clone(),
+        transition,
     });
 
     // Return conditional element
     Ok(JsExpr::ConditionalElement(context.ifs.len() - 1))
 }
 
+fn compile_await(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    let promise = find_binding_attr(attrs, "promise", span)?;
+
+    validate_all_children_are_elements(span, children)?;
+    validate_child_element_names(span, children, &["pending", "then", "catch"])?;
+
+    let pending_child = find_unique_child_by_name(children, "pending", span)?;
+    let then_child = find_unique_child_by_name(children, "then", span)?;
+    let catch_child = find_unique_child_by_name(children, "catch", span)?;
+
+    if pending_child.is_none() && then_child.is_none() && catch_child.is_none() {
+        return Err(Error {
+            message: "Missing <pending>, <then> and <catch> blocks in <await>; at least one must be present.".to_string(),
+            main_span: *span,
+            labels: vec![(*span, "Missing <pending>, <then> or <catch> block".to_string())],
+        });
+    }
+
+    // The resolved value has the promise's value type; rejections can be anything
+    let value_type = Type::Var(env.infer_ctx.fresh_point());
+    let pending = match pending_child {
+        Some(node) => Some(compile_await_branch(node, "pending", None, context, env)?),
+        None => None,
+    };
+    let then = match then_child {
+        Some(node) => Some(compile_await_branch(
+            node,
+            "then",
+            Some(value_type.clone()),
+            context,
+            env,
+        )?),
+        None => None,
+    };
+    let catch = match catch_child {
+        Some(node) => Some(compile_await_branch(
+            node,
+            "catch",
+            Some(Type::Prim("unknown".to_string())),
+            context,
+            env,
+        )?),
+        None => None,
+    };
+
+    env.infer(
+        &promise,
+        Expected::Expect(
+            Type::Promise(Box::new(value_type)),
+            Origin::attribute("await", "promise"),
+        ),
+    );
+
+    context.awaits.push(AwaitInfo {
+        promise_expr: promise,
+        pending,
+        then,
+        catch,
+    });
+
+    Ok(JsExpr::Await(context.awaits.len() - 1))
+}
+
+/// Compile one block of an <await> as a child view. Blocks that receive a value may name it
+/// with `as`.
+fn compile_await_branch(
+    node: &Node,
+    name: &str,
+    value_type: Option<Type>,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<AwaitBranch, Error> {
+    let (attrs, children, span) = expect_element(node, name)?;
+    validate_single_child(span, children)?;
+
+    let var_name = match (attrs.iter().find(|attr| attr.name == "as"), &value_type) {
+        (Some(attr), Some(_)) => Some(find_literal_attr(std::slice::from_ref(attr), "as", span)?.0),
+        (Some(attr), None) => {
+            return Err(Error {
+                message: format!("<{}> does not receive a value", name),
+                main_span: attr.span,
+                labels: vec![(attr.span, "Remove this attribute".to_string())],
+            })
+        }
+        (None, _) => None,
+    };
+
+    let mut scope = HashMap::new();
+    if let (Some(var_name), Some(value_type)) = (&var_name, value_type) {
+        scope.insert(var_name.clone(), value_type);
+    }
+    env.env.push_scope(scope);
+    let mut branch_context = CompileContext::new();
+    let root = compile_view(&children[0], &mut branch_context, env, *span);
+    env.env.pop_scope();
+
+    let view_idx = context.child_views.len();
+    context.child_views.push(CompiledView {
+        root: root?,
+        context: branch_context,
+    });
+    Ok(AwaitBranch { view_idx, var_name })
+}
+
 fn compile_switch(
     attrs: &[SpannedAttribute],
     children: &[Node],
@@ -392,8 +1623,41 @@ fn compile_switch(
     context: &mut CompileContext,
     env: &mut TypeEnv,
 ) -> Result<JsExpr, Error> {
-    // Validate 'on' binding
-    let on_binding = find_binding_attr(attrs, "on", span)?;
+    let transition = compile_transition("switch", attrs, env)?;
+
+    // Either switch on a record's discriminant field ('on'/'by'), or directly on a
+    // string literal union ('value')
+    let (on_binding, discriminant) =
+        if let Some(value_attr) = attrs.iter().find(|attr| attr.name == "value") {
+            if let Some(attr) = attrs
+                .iter()
+                .find(|attr| attr.name == "on" || attr.name == "by")
+            {
+                return Err(Error {
+                    message: format!(
+                        "'{}' cannot be combined with 'value' in <switch>",
+                        attr.name
+                    ),
+                    main_span: attr.span,
+                    labels: vec![
+                        (attr.span, "Remove this attribute".to_string()),
+                        (
+                            value_attr.span,
+                            "Switching on a literal value here".to_string(),
+                        ),
+                    ],
+                });
+            }
+            (find_binding_attr(attrs, "value", span)?, None)
+        } else {
+            let on_binding = find_binding_attr(attrs, "on", span)?;
+            let discriminant = if attrs.iter().any(|attr| attr.name == "by") {
+                find_literal_attr(attrs, "by", span)?.0
+            } else {
+                "type".to_string()
+            };
+            (on_binding, Some(discriminant))
+        };
 
     // Validate children are all <case> elements
     validate_all_children_are_elements(span, children)?;
@@ -432,25 +1696,39 @@ fn compile_switch(
             });
         }
 
-        // Bind alias with narrowed record type { type: "name", ...RowVar }
-        let tail = env.infer_ctx.fresh_row_point();
-        let mut fields = BTreeMap::new();
-        fields.insert("type".to_string(), Type::Prim(format!("\"{}\"", name)));
-        let row = env.infer_ctx.fresh_row_extend(fields, tail.clone());
-        let alias_ty = Type::Record(row);
-
-        // Push scope with alias name bound to alias_ty
-        let point = env.infer_ctx.fresh_point();
-        let mut scope = HashMap::new();
-        scope.insert(name.clone(), Type::Var(point.clone()));
-        env.env.push_scope(scope);
-        env.constraints
-            .push(Constraint::Equal(*case_span, Type::Var(point), alias_ty));
-
-        // Compile case body as a child view
         let mut sub_context = CompileContext::new();
-        let child_root = compile_view(&case_children[0], &mut sub_context, env, *case_span)?;
-        env.env.pop_scope();
+        let child_root = if let Some(discriminant) = &discriminant {
+            // Bind alias with narrowed record type { [discriminant]: "name", ...RowVar }
+            let tail = env.infer_ctx.fresh_row_point();
+            let mut fields = BTreeMap::new();
+            fields.insert(
+                discriminant.clone(),
+                string_literal_union(std::iter::once(&name)),
+            );
+            let row = env.infer_ctx.fresh_row_extend(fields, tail.clone());
+            let alias_ty = Type::Record(row);
+
+            // Push scope with alias name bound to alias_ty
+            let point = env.infer_ctx.fresh_point();
+            let mut scope = HashMap::new();
+            scope.insert(name.clone(), Type::Var(point.clone()));
+            env.env.push_scope(scope);
+            env.constraints.push(Constraint::Equal(
+                *case_span,
+                Type::Var(point),
+                alias_ty,
+                Origin::attribute("case", "as"),
+            ));
+
+            // Compile case body as a child view
+            let child_root = compile_view(&case_children[0], &mut sub_context, env, *case_span)?;
+            env.env.pop_scope();
+            union_map.insert(name.clone(), tail);
+            child_root
+        } else {
+            // Literal cases introduce no alias; the case body sees the same scope
+            compile_view(&case_children[0], &mut sub_context, env, *case_span)?
+        };
 
         let child_view_idx = context.child_views.len();
         context.child_views.push(CompiledView {
@@ -459,14 +1737,23 @@ fn compile_switch(
         });
 
         case_view_idxs.push(child_view_idx);
-        case_names.push(name.clone());
-        union_map.insert(name, tail);
+        case_names.push(name);
     }
 
-    // Unify the 'on' expression with a discriminated union of the collected cases
+    let on_type = match &discriminant {
+        // Unify the 'on' expression with a discriminated union of the collected cases
+        Some(discriminant) => Type::DiscriminatedUnion(discriminant.clone(), union_map),
+        // Unify the 'value' expression with the union of the case names as string literals
+        None => string_literal_union(case_names.iter()),
+    };
+    let on_attr = if discriminant.is_some() {
+        "on"
+    } else {
+        "value"
+    };
     env.infer(
         &on_binding,
-        Expected::Expect(Type::DiscriminatedUnion(union_map)),
+        Expected::Expect(on_type, Origin::attribute("switch", on_attr)),
     );
 
     // Track switch info in context
@@ -474,6 +1761,8 @@ fn compile_switch(
         case_view_idxs,
         case_names,
         on_expr: on_binding.clone(),
+        discriminant,
+        transition,
     });
 
     Ok(JsExpr::SwitchElement(context.switches.len() - 1))
@@ -504,7 +1793,10 @@ fn compile_use(
     for (attr_name, attr_expr) in &input_attrs {
         attr_types.insert(attr_name.clone(), env.infer(attr_expr, Expected::NoExpect));
     }
-    env.infer(&view_expr, Expected::Expect(Type::View(attr_types)));
+    env.infer(
+        &view_expr,
+        Expected::Expect(Type::View(attr_types), Origin::attribute("use", "view")),
+    );
 
     // Collect mount binding and dependencies
     let use_idx = context.use_views.len();
@@ -518,20 +1810,353 @@ fn compile_use(
     Ok(JsExpr::Use(use_idx))
 }
 
+/// Compile `<dynamic tag={tag} ...>`, an element whose tag comes from the input. Its attributes
+/// are typed as those shared by all elements.
+fn compile_dynamic(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    let tag_expr = find_binding_attr(attrs, "tag", span)?;
+    if let Some(attr) = attrs.iter().find(|attr| attr.name == "ref") {
+        return Err(Error {
+            message: "<dynamic> elements cannot have a ref".to_string(),
+            main_span: attr.span,
+            labels: vec![(
+                attr.span,
+                "The element is replaced whenever its tag changes".to_string(),
+            )],
+        });
+    }
+    env.infer(
+        &tag_expr,
+        Expected::Expect(
+            string_literal_union(known_tags()),
+            Origin::attribute(DYNAMIC_TAG, "tag"),
+        ),
+    );
+
+    let element_attrs = attrs
+        .iter()
+        .filter(|attr| attr.name != "tag")
+        .cloned()
+        .collect_vec();
+    let mut child_context = CompileContext::new();
+    let child_root = compile_element(
+        DYNAMIC_TAG,
+        &element_attrs,
+        children,
+        Some(tag_expr.clone()),
+        &mut child_context,
+        env,
+    )?;
+    let child_view_idx = context.child_views.len();
+    context.child_views.push(CompiledView {
+        root: child_root,
+        context: child_context,
+    });
+
+    context.dynamics.push(DynamicInfo {
+        child_view_idx,
+        tag_expr,
+    });
+
+    Ok(JsExpr::Dynamic(context.dynamics.len() - 1))
+}
+
+/// Compile `<provide name="theme" value={theme}>`, which makes `context.theme` available to
+/// the components called in its content.
+fn compile_provide(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    let (name, name_span) = find_literal_attr(attrs, "name", span)?;
+    let value_expr = find_binding_attr(attrs, "value", span)?;
+    validate_single_child(span, children)?;
+
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(Error {
+            message: format!("'{}' is not a valid context name", name),
+            main_span: name_span,
+            labels: vec![(name_span, "Use a name like 'theme'".to_string())],
+        });
+    }
+
+    // Inside the content, context is the enclosing context extended with the provided value
+    let value_type = env.infer(&value_expr, Expected::NoExpect);
+    let outer_context = env.infer(
+        &Expr::Variable(CONTEXT_FIELD.to_string(), *span),
+        Expected::NoExpect,
+    );
+    let outer_row = env.infer_ctx.fresh_row_point();
+    env.constraints.push(Constraint::Equal(
+        *span,
+        outer_context,
+        Type::Record(outer_row.clone()),
+        Origin::attribute("provide", "value"),
+    ));
+    let inner_row = env
+        .infer_ctx
+        .fresh_row_extend(BTreeMap::from([(name.clone(), value_type)]), outer_row);
+
+    env.env.push_scope(HashMap::from([(
+        CONTEXT_FIELD.to_string(),
+        Type::Record(inner_row),
+    )]));
+    let mut child_context = CompileContext::new();
+    let child_root = compile_view(&children[0], &mut child_context, env, *span);
+    env.env.pop_scope();
+
+    let child_view_idx = context.child_views.len();
+    context.child_views.push(CompiledView {
+        root: child_root?,
+        context: child_context,
+    });
+    context.provides.push(ProvideInfo {
+        child_view_idx,
+        name,
+        value_expr,
+    });
+
+    Ok(JsExpr::Provide(context.provides.len() - 1))
+}
+
+/// Compile `<portal target={element}>`, whose child is rendered into `element` while an anchor
+/// marks its place in this view.
+fn compile_portal(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    let target_expr = find_binding_attr(attrs, "target", span)?;
+    validate_single_child(span, children)?;
+
+    // The target is the element itself, not a selector: the portal is built before the view is
+    // attached, so a selector could silently match nothing. `HTMLElement` is a DOM type that
+    // VeGen does not model, so it is kept opaque.
+    env.infer(
+        &target_expr,
+        Expected::Expect(
+            Type::Prim("HTMLElement".to_string()),
+            Origin::attribute("portal", "target"),
+        ),
+    );
+
+    let mut child_context = CompileContext::new();
+    let child_root = compile_view(&children[0], &mut child_context, env, *span)?;
+    let child_view_idx = context.child_views.len();
+    context.child_views.push(CompiledView {
+        root: child_root,
+        context: child_context,
+    });
+
+    context.portals.push(PortalInfo {
+        child_view_idx,
+        target_expr,
+    });
+
+    Ok(JsExpr::Portal(context.portals.len() - 1))
+}
+
+/// Compile `<raw html={content}/>`, which inserts `content` as unescaped HTML. Only use it with
+/// trusted or sanitized content.
+fn compile_raw(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    context: &mut CompileContext,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    if let Some(attr) = attrs.iter().find(|attr| attr.name != "html") {
+        return Err(Error {
+            message: format!("Unexpected '{}' attribute on <raw>", attr.name),
+            main_span: attr.span,
+            labels: vec![(
+                attr.span,
+                "Only the 'html' attribute is supported.".to_string(),
+            )],
+        });
+    }
+    if !children.is_empty() {
+        return Err(Error {
+            message: "<raw> must not have children.".to_string(),
+            main_span: *span,
+            labels: vec![(*span, "Pass the content through 'html'".to_string())],
+        });
+    }
+
+    let html_expr = find_binding_attr(attrs, "html", span)?;
+    env.infer(
+        &html_expr,
+        Expected::Expect(
+            Type::Prim("string".to_string()),
+            Origin::attribute("raw", "html"),
+        ),
+    );
+
+    let raw_idx = context.raw_html.len();
+    context.updaters.push(JsUpdater {
+        dependencies: expr_dependencies(&html_expr).into_iter().collect(),
+        kind: UpdateKind::RawHtml {
+            raw_idx,
+            value: html_expr.clone(),
+        },
+    });
+    context.raw_html.push(RawHtmlInfo { html_expr });
+
+    Ok(JsExpr::RawHtml(raw_idx))
+}
+
+fn compile_slot(
+    attrs: &[SpannedAttribute],
+    children: &[Node],
+    span: &Span,
+    env: &mut TypeEnv,
+) -> Result<JsExpr, Error> {
+    if let Some(attr) = attrs.iter().find(|attr| attr.name != "name") {
+        return Err(Error {
+            message: format!("Unexpected '{}' attribute on <slot>", attr.name),
+            main_span: attr.span,
+            labels: vec![(
+                attr.span,
+                "Only the 'name' attribute is supported.".to_string(),
+            )],
+        });
+    }
+    if !children.is_empty() {
+        return Err(Error {
+            message: "<slot> must not have children.".to_string(),
+            main_span: *span,
+            labels: vec![(*span, "Remove nested content".to_string())],
+        });
+    }
+    // The slot's DOM node is owned by the caller, so it can only be placed once
+    if env.loop_depth > 0 {
+        return Err(Error {
+            message: "<slot> cannot be used inside a <for> loop".to_string(),
+            main_span: *span,
+            labels: vec![(*span, "Slot used here".to_string())],
+        });
+    }
+    let name = if attrs.is_empty() {
+        DEFAULT_SLOT.to_string()
+    } else {
+        let (name, name_span) = find_literal_attr(attrs, "name", span)?;
+        if name == DEFAULT_SLOT {
+            return Err(Error {
+                message: format!("'{}' is reserved for the unnamed slot", DEFAULT_SLOT),
+                main_span: name_span,
+                labels: vec![(name_span, "Remove the name or choose another".to_string())],
+            });
+        }
+        name
+    };
+    if let Some(previous) = env.slots.get(&name) {
+        return Err(Error {
+            message: format!("A view can only contain one <slot> named '{}'", name),
+            main_span: *span,
+            labels: vec![
+                (*span, "Second slot".to_string()),
+                (*previous, "First slot".to_string()),
+            ],
+        });
+    }
+    env.slots.insert(name.clone(), *span);
+
+    Ok(JsExpr::Slot(name))
+}
+
+/// Split a component call's children into slot name -> (content, span). `<name-slot>`
+/// elements fill named slots and any remaining children fill the default slot.
+fn split_slot_children<'a>(
+    children: &'a [Node],
+    span: &Span,
+) -> Result<BTreeMap<String, (&'a Node, Span)>, Error> {
+    let mut slots = BTreeMap::new();
+    let mut default_children = Vec::new();
+
+    for child in children {
+        let (slot_name, content, slot_span) = match child {
+            Node::Element {
+                name,
+                children: slot_children,
+                span: slot_span,
+                ..
+            } if name.ends_with("-slot") => {
+                validate_single_child(slot_span, slot_children)?;
+                let slot_name = name.strip_suffix("-slot").unwrap_or(name).to_string();
+                (slot_name, &slot_children[0], *slot_span)
+            }
+            _ => {
+                default_children.push(child);
+                continue;
+            }
+        };
+        if let Some((_, previous)) = slots.insert(slot_name.clone(), (content, slot_span)) {
+            return Err(Error {
+                message: format!("Duplicate content for slot '{}'", slot_name),
+                main_span: slot_span,
+                labels: vec![
+                    (slot_span, "Second slot content".to_string()),
+                    (previous, "First slot content".to_string()),
+                ],
+            });
+        }
+    }
+
+    if let Some(first) = default_children.first() {
+        if default_children.len() > 1 || slots.contains_key(DEFAULT_SLOT) {
+            let mut labels = vec![(*span, "Component".to_string())];
+            for child in &default_children[1..] {
+                labels.push((*child.span(), "Extraneous child element".to_string()));
+            }
+            return Err(Error {
+                message: "Default slot content must be exactly one child.".to_string(),
+                main_span: *span,
+                labels,
+            });
+        }
+        slots.insert(DEFAULT_SLOT.to_string(), (*first, *first.span()));
+    }
+
+    Ok(slots)
+}
+
 fn compile_component_call(
     name: &str,
     attrs: &[SpannedAttribute],
-    _children: &[Node],
+    children: &[Node],
     span: &Span,
     context: &mut CompileContext,
     env: &mut TypeEnv,
 ) -> Result<JsExpr, Error> {
-    // Check if component exists in stored views
-    let view_attrs = env.views.get(name).cloned().ok_or_else(|| Error {
-        message: format!("Component '{}' not found", name),
-        main_span: *span,
-        labels: vec![(*span, format!("Component '{}' is used here", name))],
-    })?;
+    // A component that failed to compile accepts whatever the caller passes
+    let failed = env.failed_views.contains(name);
+    let view_attrs = if failed {
+        attrs
+            .iter()
+            .map(|attr| (attr.name.clone(), Type::Error))
+            .collect()
+    } else {
+        // Check if component exists in stored views
+        env.views.get(name).cloned().ok_or_else(|| Error {
+            message: format!("Component '{}' not found", name),
+            main_span: *span,
+            labels: vec![(*span, format!("Component '{}' is used here", name))],
+        })?
+    };
 
     // Build map of provided attributes
     let provided_attrs: BTreeMap<_, _> = attrs
@@ -539,11 +2164,31 @@ fn compile_component_call(
         .map(|attr| (attr.name.clone(), attribute_expr(attr)))
         .collect();
 
-    let required_keys: HashSet<String> = view_attrs.keys().cloned().collect();
+    if let Some(attr) = attrs.iter().find(|attr| attr.name == CONTEXT_FIELD) {
+        return Err(Error {
+            message: format!(
+                "'{}' is passed to components implicitly; use <provide> to extend it",
+                CONTEXT_FIELD
+            ),
+            main_span: attr.span,
+            labels: vec![(attr.span, "Remove this attribute".to_string())],
+        });
+    }
+
+    let required_keys: HashSet<String> = view_attrs
+        .keys()
+        .filter(|key| *key != CONTEXT_FIELD)
+        .cloned()
+        .collect();
     let provided_keys: HashSet<String> = provided_attrs.keys().cloned().collect();
+    let optional_keys = env.view_optional.get(name).cloned().unwrap_or_default();
 
     // Check for missing attributes
-    let missing_attrs: Vec<_> = required_keys.difference(&provided_keys).cloned().collect();
+    let missing_attrs: Vec<_> = required_keys
+        .difference(&provided_keys)
+        .filter(|key| !optional_keys.contains(*key))
+        .cloned()
+        .collect();
 
     if !missing_attrs.is_empty() {
         return Err(Error {
@@ -576,7 +2221,86 @@ fn compile_component_call(
     let instantiated_view_attrs = env.infer_ctx.instantiate_attrs(&view_attrs);
     for (attr_name, attr_expr) in &provided_attrs {
         let ty = instantiated_view_attrs.get(attr_name).unwrap();
-        env.infer(attr_expr, Expected::Expect(ty.clone()));
+        let origin = Origin::ComponentParam {
+            view: name.to_string(),
+            name: attr_name.clone(),
+        };
+        env.infer(attr_expr, Expected::Expect(ty.clone(), origin));
+    }
+
+    // The caller's context, including enclosing <provide>s, must have every value the
+    // component reads from its own
+    let component_context = instantiated_view_attrs
+        .get(CONTEXT_FIELD)
+        .filter(|ty| record_fields(ty).is_some_and(|fields| !fields.is_empty()));
+    if let Some(ty) = component_context {
+        env.infer(
+            &Expr::Variable(CONTEXT_FIELD.to_string(), *span),
+            Expected::Expect(
+                ty.clone(),
+                Origin::ComponentParam {
+                    view: name.to_string(),
+                    name: CONTEXT_FIELD.to_string(),
+                },
+            ),
+        );
+    }
+
+    // Children fill the component's slots, compiled in the caller's scope
+    let view_slots = env.view_slots.get(name).cloned().unwrap_or_default();
+    let slot_contents = split_slot_children(children, span)?;
+
+    if let Some((slot_name, (_, slot_span))) = slot_contents
+        .iter()
+        .find(|(slot_name, _)| !failed && !view_slots.contains_key(*slot_name))
+    {
+        let message = if slot_name == DEFAULT_SLOT {
+            format!(
+                "Component '{}' does not accept children; its view has no <slot>",
+                name
+            )
+        } else {
+            format!("Component '{}' has no slot named '{}'", name, slot_name)
+        };
+        return Err(Error {
+            message,
+            main_span: *slot_span,
+            labels: vec![(*slot_span, "Unexpected slot content".to_string())],
+        });
+    }
+
+    let missing_slots: Vec<_> = view_slots
+        .keys()
+        .filter(|slot_name| !slot_contents.contains_key(*slot_name))
+        .map(|slot_name| {
+            if slot_name == DEFAULT_SLOT {
+                "children".to_string()
+            } else {
+                format!("<{}-slot>", slot_name)
+            }
+        })
+        .collect();
+    if !missing_slots.is_empty() {
+        return Err(Error {
+            message: format!(
+                "Component '{}' is missing content for its slots: {}",
+                name,
+                missing_slots.join(", ")
+            ),
+            main_span: *span,
+            labels: vec![(*span, "Add the missing slot content".to_string())],
+        });
+    }
+
+    let mut slots = BTreeMap::new();
+    for (slot_name, (content, slot_span)) in slot_contents {
+        let mut slot_context = CompileContext::new();
+        let slot_root = compile_view(content, &mut slot_context, env, slot_span)?;
+        slots.insert(slot_name, context.child_views.len());
+        context.child_views.push(CompiledView {
+            root: slot_root,
+            context: slot_context,
+        });
     }
 
     // Create component call info
@@ -584,6 +2308,8 @@ fn compile_component_call(
     context.component_calls.push(crate::ir::ComponentCallInfo {
         target_view_name: name.to_string(),
         input_attrs: provided_attrs,
+        slots,
+        pass_context: component_context.is_some(),
     });
 
     Ok(JsExpr::ComponentCall(component_idx))
@@ -1,9 +1,13 @@
 use crate::builtins::BUILTINS;
-use crate::ir::{CompiledView, JsExpr, JsUpdater, UpdateKind, ViewDefinition};
+use crate::ir::{
+    CompiledView, ComponentCallInfo, ElementTag, JsExpr, JsUpdater, ProvideInfo, SwitchInfo,
+    UpdateKind, ViewDefinition,
+};
 use crate::lang::{AttrValue, Expr, StringTemplateSegment};
-use crate::ts_util::render_key;
+use crate::ts_type::{name_nested_types, TsType};
+use crate::ts_util::{render_key, render_property};
 use itertools::Itertools;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 pub fn render(view: &CompiledView, indent: &str) -> String {
     let CompiledView {
@@ -29,9 +33,13 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
                     format!("{{{}}}", pairs)
                 };
                 let children_str = children.iter().map(serialize_js_expr).join(", ");
+                let tag = match tag {
+                    ElementTag::Static(tag) => format!("\"{}\"", tag),
+                    ElementTag::Dynamic(tag_expr) => render_expr(tag_expr),
+                };
 
                 if dataset.is_empty() {
-                    format!("h(\"{}\", {}, [{}])", tag, props_str, children_str)
+                    format!("h({}, {}, [{}])", tag, props_str, children_str)
                 } else {
                     let dataset_str = {
                         let pairs = dataset
@@ -42,7 +50,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
                         format!("{{{}}}", pairs)
                     };
                     format!(
-                        "h(\"{}\", {}, [{}], {})",
+                        "h({}, {}, [{}], {})",
                         tag, props_str, children_str, dataset_str
                     )
                 }
@@ -53,10 +61,16 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             }
             JsExpr::Ref(idx) => format!("node{}", idx),
             JsExpr::LoopElements(idx) => format!("...loopElements{}", idx),
+            JsExpr::RawHtml(idx) => format!("...rawElements{}", idx),
+            JsExpr::Portal(idx) => format!("portalAnchor{}", idx),
+            JsExpr::Await(idx) => format!("awaitState{}.root", idx),
+            JsExpr::Dynamic(idx) => format!("dynamicState{}.root", idx),
+            JsExpr::Provide(idx) => format!("provideState{}.root", idx),
             JsExpr::ConditionalElement(idx) => format!("conditionalElement{}", idx),
             JsExpr::SwitchElement(idx) => format!("switchElement{}", idx),
             JsExpr::Use(idx) => format!("useViewState{}.root", idx),
             JsExpr::ComponentCall(idx) => format!("componentState{}.root", idx),
+            JsExpr::Slot(name) => format!("{}.root", render_property("input.slots", name)),
         }
     }
 
@@ -93,6 +107,37 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
                     render_attr_value(value)
                 )
             }
+            UpdateKind::ClassToggle {
+                node_idx,
+                class,
+                value,
+            } => {
+                format!(
+                    "node{}.classList.toggle(\"{}\", {})",
+                    node_idx,
+                    class,
+                    render_expr(value)
+                )
+            }
+            UpdateKind::RawHtml { raw_idx, value } => {
+                format!(
+                    "replaceRawHtml(rawStart{0}, rawEnd{0}, {1})",
+                    raw_idx,
+                    render_expr(value)
+                )
+            }
+            UpdateKind::Style {
+                node_idx,
+                property,
+                value,
+            } => {
+                format!(
+                    "node{}.style.setProperty(\"{}\", {})",
+                    node_idx,
+                    property,
+                    render_attr_value(value)
+                )
+            }
         }
     }
 
@@ -107,6 +152,24 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
     // Build: create nodes from constructors, append root
     let mut build_lines = Vec::new();
 
+    // Fill in omitted optional inputs, both when building and when updating
+    let apply_defaults = (!view.input_defaults.is_empty()).then(|| {
+        let fields = view
+            .input_defaults
+            .iter()
+            .map(|(name, default)| {
+                format!(
+                    "{}: input.{} ?? {}",
+                    render_key(name),
+                    name,
+                    render_expr(default)
+                )
+            })
+            .join(", ");
+        format!("input = {{ ...input, {} }};", fields)
+    });
+    build_lines.extend(apply_defaults.clone());
+
     // Build child views
     for (i, v) in view.child_views.iter().enumerate() {
         let child_code = render(v, &format!("{}  ", indent));
@@ -148,6 +211,86 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         build_lines.push(format!("loopElements{}.push(anchor{});", i, i));
     }
 
+    // Process raw HTML (parse the initial content between two anchors)
+    for (i, raw) in view.raw_html.iter().enumerate() {
+        build_lines.push(format!(
+            "const rawStart{} = document.createComment(\"raw-html-{}\");",
+            i, i
+        ));
+        build_lines.push(format!(
+            "const rawEnd{} = document.createComment(\"/raw-html-{}\");",
+            i, i
+        ));
+        build_lines.push(format!(
+            "const rawElements{0} = [rawStart{0}, ...parseRawHtml({1}), rawEnd{0}];",
+            i,
+            render_expr(&raw.html_expr)
+        ));
+    }
+
+    // Process portals (build the child view into its target, leaving an anchor in place)
+    for (i, portal) in view.portals.iter().enumerate() {
+        build_lines.push(format!(
+            "const portalAnchor{} = document.createComment(\"portal-{}\");",
+            i, i
+        ));
+        build_lines.push(format!(
+            "const portalState{} = child{}(input);",
+            i, portal.child_view_idx
+        ));
+        build_lines.push(format!(
+            "{}.append(portalState{}.root);",
+            render_expr(&portal.target_expr),
+            i
+        ));
+    }
+
+    // Process awaits (subscribe to the initial promise)
+    for (i, await_info) in view.awaits.iter().enumerate() {
+        let branches = [
+            ("pending", &await_info.pending),
+            ("then", &await_info.then),
+            ("catch", &await_info.catch),
+        ]
+        .into_iter()
+        .filter_map(|(name, branch)| {
+            let branch = branch.as_ref()?;
+            let input = match &branch.var_name {
+                Some(var) => format!("(input, value) => ({{ ...input, {}: value }})", var),
+                None => "(input) => input".to_string(),
+            };
+            Some(format!(
+                "{}: {{ view: child{}, input: {} }}",
+                name, branch.view_idx, input
+            ))
+        })
+        .join(", ");
+        build_lines.push(format!(
+            "const awaitState{} = awaitPromise({}, input, {{ {} }});",
+            i,
+            render_expr(&await_info.promise_expr),
+            branches
+        ));
+    }
+
+    // Process dynamic elements (build with the initial tag)
+    for (i, dynamic) in view.dynamics.iter().enumerate() {
+        build_lines.push(format!(
+            "let dynamicState{} = child{}(input);",
+            i, dynamic.child_view_idx
+        ));
+    }
+
+    // Process provides (build the content with the extended context)
+    for (i, provide) in view.provides.iter().enumerate() {
+        build_lines.push(format!(
+            "const provideState{} = child{}({});",
+            i,
+            provide.child_view_idx,
+            render_provide_input(provide)
+        ));
+    }
+
     // Process ifs (initialize current state and element)
     for (i, if_info) in view.ifs.iter().enumerate() {
         build_lines.push(format!("let currentState{}: ViewState<any>;", i));
@@ -157,7 +300,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             build_lines.push(format!("  currentState{} = child{}(input);", i, then_idx));
         } else {
             build_lines.push(format!(
-                "  currentState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}} }};",
+                "  currentState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}}, destroy: () => {{}} }};",
                 i
             ));
         }
@@ -166,7 +309,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             build_lines.push(format!("  currentState{} = child{}(input);", i, else_idx));
         } else {
             build_lines.push(format!(
-                "  currentState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}} }};",
+                "  currentState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}}, destroy: () => {{}} }};",
                 i
             ));
         }
@@ -182,17 +325,16 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         build_lines.push(format!("let currentSwitchState{}: ViewState<any>;", i));
         build_lines.push(format!("const switchElement{} = (() => {{", i));
         build_lines.push(format!(
-            "  const onValue = {}.type;",
-            render_expr(&switch_info.on_expr)
+            "  const onValue = {};",
+            render_switch_value(switch_info, "input")
         ));
         build_lines.push("  switch (onValue) {".to_string());
         for (j, case_name) in switch_info.case_names.iter().enumerate() {
             let case_idx = switch_info.case_view_idxs[j];
             build_lines.push(format!("    case \"{}\": {{", case_name));
             build_lines.push(format!(
-                "      const caseInput = {{ ...input, {}: {} }};",
-                case_name,
-                render_expr(&switch_info.on_expr)
+                "      const caseInput = {};",
+                render_case_input(switch_info, case_name)
             ));
             build_lines.push(format!("      const st = child{}(caseInput);", case_idx));
             build_lines.push(format!("      currentSwitchState{} = st;", i));
@@ -201,7 +343,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         }
         build_lines.push("    default: {".to_string());
         build_lines.push(
-            "      const st = { root: document.createComment(\"switch-empty\"), update: (_: any) => {} };"
+            "      const st = { root: document.createComment(\"switch-empty\"), update: (_: any) => {}, destroy: () => {} };"
                 .to_string(),
         );
         build_lines.push(format!("      currentSwitchState{} = st;", i));
@@ -221,13 +363,24 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         ));
     }
 
-    // Process component calls (instantiate component views)
+    // Process component calls (instantiate slot content and component views)
     for (i, component_call) in view.component_calls.iter().enumerate() {
+        if !component_call.slots.is_empty() {
+            let slot_states = component_call
+                .slots
+                .iter()
+                .map(|(name, idx)| format!("{}: child{}(input)", render_key(name), idx))
+                .join(", ");
+            build_lines.push(format!(
+                "const componentSlots{} = {{ {} }};",
+                i, slot_states
+            ));
+        }
         build_lines.push(format!(
             "const componentState{} = {}({});",
             i,
             component_call.target_view_name,
-            render_object(&component_call.input_attrs)
+            render_component_input(component_call, i)
         ));
     }
 
@@ -236,13 +389,43 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         build_lines.push(format!("const node{} = {};", i, serialize_js_expr(expr)));
     }
 
+    // Directives are not element properties, so they are applied once their element exists
+    for kind in &view.directives {
+        build_lines.push(format!("{};", serialize_update(kind)));
+    }
+
     // Create root
     build_lines.push(format!("const root = {};", serialize_js_expr(root)));
 
+    // Two-way bindings write the property back through the current input's setter
+    for binding in &view.bindings {
+        build_lines.push(format!(
+            "node{idx}.addEventListener(\"{event}\", () => currentInput.{setter}(node{idx}.{prop}));",
+            idx = binding.node_idx,
+            event = binding.event,
+            setter = binding.setter,
+            prop = binding.prop
+        ));
+    }
+
+    // Mount hooks run once the caller has had a chance to attach the view to the document
+    if !view.mount_hooks.is_empty() {
+        build_lines.push("queueMicrotask(() => {".to_string());
+        for hook in &view.mount_hooks {
+            build_lines.push(format!(
+                "  ({})(node{});",
+                render_expr(&hook.handler),
+                hook.node_idx
+            ));
+        }
+        build_lines.push("});".to_string());
+    }
+
     build_lines.push("let currentInput = input;".to_string());
 
     // Update: group updaters by dependencies
     let mut update_lines = Vec::new();
+    update_lines.extend(apply_defaults);
     let mut grouped: BTreeMap<Vec<String>, Vec<&JsUpdater>> = BTreeMap::new();
     for updater in &view.updaters {
         let mut deps = updater.dependencies.clone();
@@ -277,6 +460,9 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             seq = render_expr(&for_loop.sequence_expr),
             var = for_loop.var_name
         ));
+        if let Some(transition) = &for_loop.transition {
+            update_lines.push(format!("  transition: {},", render_expr(transition)));
+        }
         update_lines.push(format!("  subView: child{}", for_loop.child_view_idx));
         update_lines.push("});".to_string());
     }
@@ -299,6 +485,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             render_expr(&use_info.view_expr),
             input_obj
         ));
+        update_lines.push(format!("  useViewState{}.destroy();", i));
         update_lines.push(format!(
             "  useViewState{}.root.replaceWith(newUseViewState{}.root);",
             i, i
@@ -311,13 +498,76 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
 
     // Add component call update logic
     for (i, component_call) in view.component_calls.iter().enumerate() {
+        // Slot content is bound to this view's input, so it is updated here
+        for name in component_call.slots.keys() {
+            update_lines.push(format!(
+                "{}.update(input);",
+                render_property(&format!("componentSlots{}", i), name)
+            ));
+        }
         update_lines.push(format!(
             "componentState{}.update({});",
             i,
-            render_object(&component_call.input_attrs)
+            render_component_input(component_call, i)
         ));
     }
 
+    // Add dynamic element update logic, rebuilding the element if its tag changed
+    for (i, dynamic) in view.dynamics.iter().enumerate() {
+        update_lines.push(format!(
+            "if ({} !== {}) {{",
+            render_expr(&dynamic.tag_expr),
+            render_expr_with_global_object(&dynamic.tag_expr, "currentInput"),
+        ));
+        update_lines.push(format!(
+            "  const newDynamicState{} = child{}(input);",
+            i, dynamic.child_view_idx
+        ));
+        update_lines.push(format!("  dynamicState{}.destroy();", i));
+        update_lines.push(format!(
+            "  dynamicState{0}.root.replaceWith(newDynamicState{0}.root);",
+            i
+        ));
+        update_lines.push(format!("  dynamicState{0} = newDynamicState{0};", i));
+        update_lines.push("} else {".to_string());
+        update_lines.push(format!("  dynamicState{}.update(input);", i));
+        update_lines.push("}".to_string());
+    }
+
+    // Add provide update logic
+    for (i, provide) in view.provides.iter().enumerate() {
+        update_lines.push(format!(
+            "provideState{}.update({});",
+            i,
+            render_provide_input(provide)
+        ));
+    }
+
+    // Add await update logic; a new promise shows <pending> again
+    for (i, await_info) in view.awaits.iter().enumerate() {
+        update_lines.push(format!(
+            "awaitState{}.update(input, {});",
+            i,
+            render_expr(&await_info.promise_expr)
+        ));
+    }
+
+    // Add portal update logic, moving the content if the target changed
+    for (i, portal) in view.portals.iter().enumerate() {
+        update_lines.push(format!(
+            "if ({} !== {}) {{",
+            render_expr(&portal.target_expr),
+            render_expr_with_global_object(&portal.target_expr, "currentInput"),
+        ));
+        update_lines.push(format!(
+            "  {}.append(portalState{}.root);",
+            render_expr(&portal.target_expr),
+            i
+        ));
+        update_lines.push("}".to_string());
+        update_lines.push(format!("portalState{}.update(input);", i));
+    }
+
     // Add if update logic
     for (i, if_info) in view.ifs.iter().enumerate() {
         update_lines.push(format!(
@@ -334,7 +584,7 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             update_lines.push(format!("    newState{} = child{}(input);", i, then_idx));
         } else {
             update_lines.push(format!(
-                    "    newState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}} }};",
+                    "    newState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}}, destroy: () => {{}} }};",
                     i
                 ));
         }
@@ -343,15 +593,20 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             update_lines.push(format!("    newState{} = child{}(input);", i, else_idx));
         } else {
             update_lines.push(format!(
-                    "    newState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}} }};",
+                    "    newState{} = {{ root: document.createComment(\"empty\"), update: (_: any) => {{}}, destroy: () => {{}} }};",
                     i
                 ));
         }
         update_lines.push("  }".to_string());
         update_lines.push(format!("  const newRoot{} = newState{}.root;", i, i));
+        update_lines.push(format!("  currentState{}.destroy();", i));
         update_lines.push(format!(
-            "  currentState{}.root.replaceWith(newRoot{});",
-            i, i
+            "  {}",
+            render_replace_root(
+                &format!("currentState{}.root", i),
+                &format!("newRoot{}", i),
+                &if_info.transition
+            )
         ));
         update_lines.push(format!("  currentState{} = newState{};", i, i));
         update_lines.push("} else {".to_string());
@@ -362,14 +617,14 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
     // Add switch update logic
     for (i, switch_info) in view.switches.iter().enumerate() {
         update_lines.push(format!(
-            "const newOnValue{} = {}.type;",
+            "const newOnValue{} = {};",
             i,
-            render_expr(&switch_info.on_expr)
+            render_switch_value(switch_info, "input")
         ));
         update_lines.push(format!(
-            "const prevOnValue{} = {}.type;",
+            "const prevOnValue{} = {};",
             i,
-            render_expr_with_global_object(&switch_info.on_expr, "currentInput")
+            render_switch_value(switch_info, "currentInput")
         ));
         update_lines.push(format!("if (newOnValue{} !== prevOnValue{}) {{", i, i));
         update_lines.push(format!("  let newState{}: ViewState<any>;", i));
@@ -379,9 +634,8 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             let case_idx = switch_info.case_view_idxs[j];
             update_lines.push(format!("    case \"{}\": {{", case_name));
             update_lines.push(format!(
-                "      const caseInput = {{ ...input, {}: {} }};",
-                case_name,
-                render_expr(&switch_info.on_expr)
+                "      const caseInput = {};",
+                render_case_input(switch_info, case_name)
             ));
             update_lines.push(format!(
                 "      newState{} = child{}(caseInput);",
@@ -392,13 +646,18 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
             update_lines.push("    }".to_string());
         }
         update_lines.push("    default: {".to_string());
-        update_lines.push(format!("      newState{} = {{ root: document.createComment(\"switch-empty\"), update: (_: any) => {{}} }};", i));
+        update_lines.push(format!("      newState{} = {{ root: document.createComment(\"switch-empty\"), update: (_: any) => {{}}, destroy: () => {{}} }};", i));
         update_lines.push(format!("      newRoot{} = newState{}.root;", i, i));
         update_lines.push("    }".to_string());
         update_lines.push("  }".to_string());
+        update_lines.push(format!("  currentSwitchState{}.destroy();", i));
         update_lines.push(format!(
-            "  currentSwitchState{}.root.replaceWith(newRoot{});",
-            i, i
+            "  {}",
+            render_replace_root(
+                &format!("currentSwitchState{}.root", i),
+                &format!("newRoot{}", i),
+                &switch_info.transition
+            )
         ));
         update_lines.push(format!("  currentSwitchState{} = newState{};", i, i));
         update_lines.push("} else {".to_string());
@@ -406,9 +665,8 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
         for case_name in switch_info.case_names.iter() {
             update_lines.push(format!("    case \"{}\": {{", case_name));
             update_lines.push(format!(
-                "      const caseInput = {{ ...input, {}: {} }};",
-                case_name,
-                render_expr(&switch_info.on_expr)
+                "      const caseInput = {};",
+                render_case_input(switch_info, case_name)
             ));
             update_lines.push(format!("      currentSwitchState{}.update(caseInput);", i));
             update_lines.push("      break;".to_string());
@@ -423,38 +681,213 @@ pub fn render(view: &CompiledView, indent: &str) -> String {
 
     update_lines.push("currentInput = input;".to_string());
 
+    // Destroy: release owned child views, then run this view's destroy hooks
+    let mut destroy_lines = Vec::new();
+    for for_loop in &view.for_loops {
+        destroy_lines.push(format!(
+            "childState{}.forEach((state) => state.destroy());",
+            for_loop.child_view_idx
+        ));
+    }
+    for i in 0..view.ifs.len() {
+        destroy_lines.push(format!("currentState{}.destroy();", i));
+    }
+    for i in 0..view.switches.len() {
+        destroy_lines.push(format!("currentSwitchState{}.destroy();", i));
+    }
+    for i in 0..view.use_views.len() {
+        destroy_lines.push(format!("useViewState{}.destroy();", i));
+    }
+    for i in 0..view.awaits.len() {
+        destroy_lines.push(format!("awaitState{}.destroy();", i));
+    }
+    for i in 0..view.dynamics.len() {
+        destroy_lines.push(format!("dynamicState{}.destroy();", i));
+    }
+    for i in 0..view.provides.len() {
+        destroy_lines.push(format!("provideState{}.destroy();", i));
+    }
+    // Portal content lives outside this view's root, so it is removed explicitly
+    for i in 0..view.portals.len() {
+        destroy_lines.push(format!("portalState{}.destroy();", i));
+        destroy_lines.push(format!("portalState{}.root.remove();", i));
+    }
+    for (i, component_call) in view.component_calls.iter().enumerate() {
+        // Slot content belongs to this view, not to the component
+        for name in component_call.slots.keys() {
+            destroy_lines.push(format!(
+                "{}.destroy();",
+                render_property(&format!("componentSlots{}", i), name)
+            ));
+        }
+        destroy_lines.push(format!("componentState{}.destroy();", i));
+    }
+    for hook in &view.destroy_hooks {
+        destroy_lines.push(format!(
+            "({})(node{});",
+            render_expr_with_global_object(&hook.handler, "currentInput"),
+            hook.node_idx
+        ));
+    }
+
     // Apply indentation and assemble final output
     let indented_build_lines = apply_indent(&build_lines, indent, "  ");
     let indented_update_lines = apply_indent(&update_lines, indent, "      ");
+    let indented_destroy_lines = apply_indent(&destroy_lines, indent, "      ");
+
+    let refs = if view.refs.is_empty() {
+        String::new()
+    } else {
+        let fields = view
+            .refs
+            .iter()
+            .map(|(name, ref_info)| format!("{}: node{}", render_key(name), ref_info.node_idx))
+            .join(", ");
+        format!("{}    refs: {{ {} }},\n", indent, fields)
+    };
+
+    let destroy = if destroy_lines.is_empty() {
+        format!("{}    destroy() {{}}\n", indent)
+    } else {
+        format!(
+            "{i}    destroy() {{\n{lines}\n{i}    }}\n",
+            i = indent,
+            lines = indented_destroy_lines.join("\n")
+        )
+    };
 
     format!(
-        "{build}\n{i}  return {{\n{i}    root,\n{i}    update(input) {{\n{update}\n{i}    }}\n  {i}}};",
+        "{build}\n{i}  return {{\n{i}    root,\n{refs}{i}    update(input) {{\n{update}\n{i}    }},\n{destroy}  {i}}};",
         i = indent,
         build = indented_build_lines.join("\n"),
+        refs = refs,
         update = indented_update_lines.join("\n"),
+        destroy = destroy,
     )
 }
 
-pub fn emit_views(views: &[ViewDefinition]) -> String {
+pub fn emit_views(
+    views: &[ViewDefinition],
+    type_imports: &BTreeMap<String, BTreeSet<String>>,
+) -> String {
     let mut output = String::new();
     output.push_str("// Generated by VeGen. Do not edit.\n");
 
     // Sometimes we emit unused functions and variables, so disable type checking
     output.push_str("// @ts-nocheck\n\n");
 
+    for (from, names) in type_imports {
+        output.push_str(&format!(
+            "import type {{ {} }} from \"{}\";\n",
+            names.iter().join(", "),
+            from
+        ));
+    }
+    if !type_imports.is_empty() {
+        output.push('\n');
+    }
+
     output.push_str(include_str!("lib.ts"));
 
+    // Nested types are named after their path unless that name is taken
+    let mut taken: HashSet<String> = views
+        .iter()
+        .flat_map(|view_def| {
+            [
+                view_input_type_name(&view_def.view_name),
+                view_refs_type_name(&view_def.view_name),
+            ]
+            .into_iter()
+            .chain(view_def.aliases.values().cloned())
+        })
+        .chain(type_imports.values().flatten().cloned())
+        .collect();
+
     for view_def in views {
         let ViewDefinition {
             view_name,
             root,
             context,
+            generics,
+            inferred_generics,
             ts_type,
+            aliases,
+            readonly,
         } = view_def;
-        let input_type_name = view_input_type_name(view_name);
-        let type_str = ts_type.to_string();
-        let input_type = format!("export type {} = {};\n", input_type_name, type_str);
+        let type_params = if generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", generics.join(", "))
+        };
+        let input_type_name = format!("{}{}", view_input_type_name(view_name), type_params);
+        // Inferred type parameters default to any, so `TodoInput` can be written without them
+        let declare_type = |name: &str, params: &[String]| {
+            if params.is_empty() {
+                return name.to_string();
+            }
+            let params = params
+                .iter()
+                .map(|param| {
+                    if inferred_generics.contains(param) {
+                        format!("{} = any", param)
+                    } else {
+                        param.clone()
+                    }
+                })
+                .join(", ");
+            format!("{}<{}>", name, params)
+        };
+        let (ts_type, nested_types) = name_nested_types(
+            ts_type,
+            &view_input_type_name(view_name),
+            generics,
+            aliases,
+            &mut taken,
+        );
+        let render_type = |ty: &TsType| {
+            if *readonly {
+                format!("{:#}", ty)
+            } else {
+                ty.to_string()
+            }
+        };
+        let input_type = format!(
+            "export type {} = {};\n",
+            declare_type(&view_input_type_name(view_name), generics),
+            render_type(&ts_type)
+        );
         output.push_str(&input_type);
+        for alias in nested_types {
+            output.push_str(&format!(
+                "export type {} = {};\n",
+                declare_type(&alias.name, &alias.params),
+                render_type(&alias.ty)
+            ));
+        }
+        let state_type = if context.refs.is_empty() {
+            format!("ViewState<{}>", input_type_name)
+        } else {
+            let refs_type_name = view_refs_type_name(view_name);
+            let fields = context
+                .refs
+                .iter()
+                .map(|(name, ref_info)| {
+                    format!(
+                        "{}: HTMLElementTagNameMap[\"{}\"]",
+                        render_key(name),
+                        ref_info.tag
+                    )
+                })
+                .join(", ");
+            output.push_str(&format!(
+                "export type {} = {{ {} }};\n",
+                refs_type_name, fields
+            ));
+            format!(
+                "ViewState<{}> & {{ refs: {} }}",
+                input_type_name, refs_type_name
+            )
+        };
         let js_code = render(
             &CompiledView {
                 root: root.clone(),
@@ -463,8 +896,11 @@ pub fn emit_views(views: &[ViewDefinition]) -> String {
             "",
         );
         output.push_str(&format!(
-            "export function {}(input: {}): ViewState<{}> {{\n{}\n}}\n",
-            view_name, input_type_name, input_type_name, js_code
+            "export function {}(input: {}): {} {{\n{}\n}}\n",
+            declare_type(view_name, generics),
+            input_type_name,
+            state_type,
+            js_code
         ));
     }
     output
@@ -478,6 +914,33 @@ pub fn render_object(obj: &BTreeMap<String, Expr>) -> String {
     format!("{{{}}}", fields)
 }
 
+fn render_component_input(component_call: &ComponentCallInfo, idx: usize) -> String {
+    if component_call.slots.is_empty() && !component_call.pass_context {
+        return render_object(&component_call.input_attrs);
+    }
+    let mut fields = component_call
+        .input_attrs
+        .iter()
+        .map(|(k, v)| format!("{}: {}", render_key(k), render_expr(v)))
+        .collect_vec();
+    if component_call.pass_context {
+        fields.push("context: input.context".to_string());
+    }
+    if !component_call.slots.is_empty() {
+        fields.push(format!("slots: componentSlots{}", idx));
+    }
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// The input of a `<provide>`'s content: this view's input with the value added to `context`.
+fn render_provide_input(provide: &ProvideInfo) -> String {
+    format!(
+        "{{ ...input, context: {{ ...input.context, {}: {} }} }}",
+        render_key(&provide.name),
+        render_expr(&provide.value_expr)
+    )
+}
+
 pub fn render_expr(expr: &Expr) -> String {
     render_expr_with_global_object(expr, "input")
 }
@@ -491,6 +954,13 @@ fn render_expr_with_global_object(expr: &Expr, global_object: &'static str) -> S
             format!("{}.{}", global_object, name)
         }
         Expr::Number(n, _) => n.clone(),
+        Expr::Array(elems, _) => format!(
+            "[{}]",
+            elems
+                .iter()
+                .map(|e| render_expr_with_global_object(e, global_object))
+                .join(", ")
+        ),
         Expr::Field(f, field, _) => {
             format!(
                 "{}.{}",
@@ -549,6 +1019,43 @@ fn render_expr_with_global_object(expr: &Expr, global_object: &'static str) -> S
     }
 }
 
+/// Swap a branch's root for a new one, leaving the old root in place until a transition's
+/// leave hook has finished with it.
+fn render_replace_root(old_root: &str, new_root: &str, transition: &Option<Expr>) -> String {
+    match transition {
+        Some(transition) => format!(
+            "replaceRoot({}, {}, {});",
+            old_root,
+            new_root,
+            render_expr(transition)
+        ),
+        None => format!("{}.replaceWith({});", old_root, new_root),
+    }
+}
+
+/// The value a <switch> dispatches on: the discriminant field, or the value itself for
+/// literal unions.
+fn render_switch_value(switch_info: &SwitchInfo, global_object: &'static str) -> String {
+    let on = render_expr_with_global_object(&switch_info.on_expr, global_object);
+    match &switch_info.discriminant {
+        Some(field) => render_property(&on, field),
+        None => on,
+    }
+}
+
+/// The input passed to a <switch> case view, binding the narrowed value to the case name
+/// when switching on a discriminated union.
+fn render_case_input(switch_info: &SwitchInfo, case_name: &str) -> String {
+    match &switch_info.discriminant {
+        Some(_) => format!(
+            "{{ ...input, {}: {} }}",
+            case_name,
+            render_expr(&switch_info.on_expr)
+        ),
+        None => "input".to_string(),
+    }
+}
+
 fn render_attr_value(attr_value: &AttrValue) -> String {
     match attr_value {
         AttrValue::Template(segments) => {
@@ -585,3 +1092,7 @@ fn render_attr_value(attr_value: &AttrValue) -> String {
 pub fn view_input_type_name(view_name: &str) -> String {
     format!("{}Input", view_name)
 }
+
+pub fn view_refs_type_name(view_name: &str) -> String {
+    format!("{}Refs", view_name)
+}
@@ -85,6 +85,83 @@ where
     Ok(())
 }
 
+/// Group nodes into strongly connected components using Tarjan's algorithm. Each component
+/// is listed after the components it depends on.
+pub fn strongly_connected_components<K>(deps: &HashMap<K, HashSet<K>>) -> Vec<Vec<K>>
+where
+    K: Eq + Hash + Clone + Ord,
+{
+    struct Tarjan<'a, K> {
+        deps: &'a HashMap<K, HashSet<K>>,
+        index: HashMap<K, usize>,
+        low_link: HashMap<K, usize>,
+        stack: Vec<K>,
+        on_stack: HashSet<K>,
+        components: Vec<Vec<K>>,
+    }
+
+    impl<K: Eq + Hash + Clone + Ord> Tarjan<'_, K> {
+        fn visit(&mut self, node: &K) {
+            let index = self.index.len();
+            self.index.insert(node.clone(), index);
+            self.low_link.insert(node.clone(), index);
+            self.stack.push(node.clone());
+            self.on_stack.insert(node.clone());
+
+            let mut children: Vec<&K> = self
+                .deps
+                .get(node)
+                .map(|children| children.iter().collect())
+                .unwrap_or_default();
+            children.sort();
+
+            for child in children {
+                if !self.index.contains_key(child) {
+                    self.visit(child);
+                    let low = self.low_link[node].min(self.low_link[child]);
+                    self.low_link.insert(node.clone(), low);
+                } else if self.on_stack.contains(child) {
+                    let low = self.low_link[node].min(self.index[child]);
+                    self.low_link.insert(node.clone(), low);
+                }
+            }
+
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    let done = &member == node;
+                    component.push(member);
+                    if done {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        deps,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+
+    let mut nodes: Vec<&K> = deps.keys().collect();
+    nodes.sort();
+    for node in nodes {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan.components
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,6 +190,21 @@ mod tests {
         assert!(cycle.nodes.contains(&"B"));
     }
 
+    #[test]
+    fn strongly_connected_components_groups_cycles_after_dependencies() {
+        let mut deps: HashMap<&str, HashSet<&str>> = HashMap::new();
+        deps.insert("App", HashSet::from(["Tree"]));
+        deps.insert("Tree", HashSet::from(["Branch", "Leaf"]));
+        deps.insert("Branch", HashSet::from(["Tree"]));
+        deps.insert("Leaf", HashSet::new());
+
+        let components = strongly_connected_components(&deps);
+        assert_eq!(
+            components,
+            vec![vec!["Leaf"], vec!["Branch", "Tree"], vec!["App"]]
+        );
+    }
+
     #[test]
     fn cycle_from_stack_returns_suffix_starting_at_repeat() {
         let stack = vec!["root", "a", "b"];
@@ -1,4 +1,4 @@
-use crate::lang::{AttrValue, Expr};
+use crate::lang::{AttrValue, Expr, Span};
 use crate::ts_type::TsType;
 
 use std::collections::BTreeMap;
@@ -7,7 +7,15 @@ use std::collections::BTreeMap;
 pub struct ViewDefinition {
     pub view_name: String,
     pub context: CompileContext,
+    /// Type parameters of the input type, e.g. `T` in `ListInput<T>`
+    pub generics: Vec<String>,
+    /// The type parameters that were inferred rather than declared, which default to `any`
+    pub inferred_generics: Vec<String>,
     pub ts_type: TsType,
+    /// Names given to the object types of inputs with `alias`, by input
+    pub aliases: BTreeMap<String, String>,
+    /// Whether the input types are emitted with readonly arrays and fields
+    pub readonly: bool,
     pub root: JsExpr,
 }
 
@@ -21,6 +29,27 @@ pub struct CompileContext {
     pub switches: Vec<SwitchInfo>,
     pub component_calls: Vec<ComponentCallInfo>,
     pub use_views: Vec<UseInfo>,
+    /// `<raw>` forms, whose content is parsed from an HTML string without escaping
+    pub raw_html: Vec<RawHtmlInfo>,
+    /// `<portal>` forms, whose child view is appended to another element
+    pub portals: Vec<PortalInfo>,
+    /// `<await>` forms, which show a branch for each state of a promise
+    pub awaits: Vec<AwaitInfo>,
+    /// `<dynamic>` elements, whose tag name is chosen at runtime
+    pub dynamics: Vec<DynamicInfo>,
+    /// `<provide>` forms, which add a value to the context passed to components
+    pub provides: Vec<ProvideInfo>,
+    /// Defaults applied to the view's input before building and updating (top-level views only)
+    pub input_defaults: BTreeMap<String, Expr>,
+    /// Elements marked with `ref`, exposed on the view's state
+    pub refs: BTreeMap<String, RefInfo>,
+    /// `onmount` and `ondestroy` handlers, called with their element
+    pub mount_hooks: Vec<HookInfo>,
+    pub destroy_hooks: Vec<HookInfo>,
+    /// `bind:` attributes, which write the element's property back through a setter
+    pub bindings: Vec<BindingInfo>,
+    /// `class:` and `style:` directives, applied once their element is built
+    pub directives: Vec<UpdateKind>,
 }
 
 impl CompileContext {
@@ -34,6 +63,17 @@ impl CompileContext {
             switches: Vec::new(),
             component_calls: Vec::new(),
             use_views: Vec::new(),
+            raw_html: Vec::new(),
+            portals: Vec::new(),
+            awaits: Vec::new(),
+            dynamics: Vec::new(),
+            provides: Vec::new(),
+            input_defaults: BTreeMap::new(),
+            refs: BTreeMap::new(),
+            mount_hooks: Vec::new(),
+            destroy_hooks: Vec::new(),
+            bindings: Vec::new(),
+            directives: Vec::new(),
         }
     }
 }
@@ -44,10 +84,17 @@ pub struct CompiledView {
     pub context: CompileContext,
 }
 
+#[derive(Debug, Clone)]
+pub enum ElementTag {
+    Static(String),
+    /// The tag of a `<dynamic>` element, computed from the input
+    Dynamic(Expr),
+}
+
 #[derive(Debug, Clone)]
 pub enum JsExpr {
     Element {
-        tag: String,
+        tag: ElementTag,
         props: Vec<(String, AttrValue)>,
         dataset: Vec<(String, AttrValue)>,
         children: Vec<JsExpr>,
@@ -56,10 +103,16 @@ pub enum JsExpr {
     Expr(Expr),
     Ref(usize),
     LoopElements(usize),
+    RawHtml(usize),
+    Portal(usize),
+    Await(usize),
+    Dynamic(usize),
+    Provide(usize),
     ConditionalElement(usize),
     SwitchElement(usize),
     Use(usize),
     ComponentCall(usize),
+    Slot(String),
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +131,23 @@ pub enum UpdateKind {
         key: String,
         value: AttrValue,
     },
+    /// `class:name={flag}`, toggling a single class
+    ClassToggle {
+        node_idx: usize,
+        class: String,
+        value: Expr,
+    },
+    /// `style:property={value}`, setting a single style property
+    Style {
+        node_idx: usize,
+        property: String,
+        value: AttrValue,
+    },
+    /// `<raw html={content}/>`, replacing everything between the form's anchors
+    RawHtml {
+        raw_idx: usize,
+        value: Expr,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -91,6 +161,7 @@ pub struct ForLoopInfo {
     pub child_view_idx: usize,
     pub sequence_expr: Expr,
     pub var_name: String,
+    pub transition: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +169,22 @@ pub struct IfInfo {
     pub then_view_idx: Option<usize>,
     pub else_view_idx: Option<usize>,
     pub condition_expr: Expr,
+    pub transition: Option<Expr>,
+}
+
+/// A branch of an <await>, with the variable its view receives the settled value in
+#[derive(Debug, Clone)]
+pub struct AwaitBranch {
+    pub view_idx: usize,
+    pub var_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AwaitInfo {
+    pub promise_expr: Expr,
+    pub pending: Option<AwaitBranch>,
+    pub then: Option<AwaitBranch>,
+    pub catch: Option<AwaitBranch>,
 }
 
 #[derive(Debug, Clone)]
@@ -105,12 +192,67 @@ pub struct SwitchInfo {
     pub case_view_idxs: Vec<usize>,
     pub case_names: Vec<String>,
     pub on_expr: Expr,
+    /// Field holding the case name, or `None` when `on_expr` is itself a string literal union
+    pub discriminant: Option<String>,
+    pub transition: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ComponentCallInfo {
     pub target_view_name: String,
     pub input_attrs: BTreeMap<String, Expr>,
+    /// Slot name -> index of the child view rendering the caller's content
+    pub slots: BTreeMap<String, usize>,
+    /// Whether the component reads `context`, which is then passed along implicitly
+    pub pass_context: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RefInfo {
+    pub node_idx: usize,
+    pub tag: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct HookInfo {
+    pub node_idx: usize,
+    pub handler: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct BindingInfo {
+    pub node_idx: usize,
+    pub prop: String,
+    pub event: String,
+    /// Input function called with the property's new value, e.g. `setNewTodoText`
+    pub setter: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RawHtmlInfo {
+    pub html_expr: Expr,
+}
+
+/// A `<dynamic>` element, compiled as a child view that is rebuilt when its tag changes
+#[derive(Debug, Clone)]
+pub struct DynamicInfo {
+    pub child_view_idx: usize,
+    pub tag_expr: Expr,
+}
+
+/// A `<provide>`, whose content is a child view receiving the extended `context`
+#[derive(Debug, Clone)]
+pub struct ProvideInfo {
+    pub child_view_idx: usize,
+    pub name: String,
+    pub value_expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct PortalInfo {
+    pub child_view_idx: usize,
+    pub target_expr: Expr,
 }
 
 #[derive(Debug, Clone)]
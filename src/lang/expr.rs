@@ -14,6 +14,7 @@ pub enum Expr {
     StringTemplate(Vec<StringTemplateSegment>, Span),
     Variable(String, Span),
     Number(String, Span),
+    Array(Vec<Expr>, Span),
     Field(Box<Expr>, String, Span),
     FunctionCall {
         callee: Box<Expr>,
@@ -33,6 +34,7 @@ impl Expr {
             Expr::StringTemplate(_, span) => span,
             Expr::Variable(_, span) => span,
             Expr::Number(_, span) => span,
+            Expr::Array(_, span) => span,
             Expr::Field(_, _, span) => span,
             Expr::FunctionCall { span, .. } => span,
             Expr::Pipe { span, .. } => span,
@@ -137,6 +139,16 @@ pub fn expr_parser<'a>(
             identifier
                 .clone()
                 .map_with(move |name, e| Expr::Variable(name, sourced_span(source, e.span()))),
+            just('[')
+                .ignore_then(
+                    expr.clone()
+                        .padded()
+                        .separated_by(just(','))
+                        .allow_trailing()
+                        .collect::<Vec<Expr>>(),
+                )
+                .then_ignore(just(']'))
+                .map_with(move |elems, e| Expr::Array(elems, sourced_span(source, e.span()))),
             just('(').ignore_then(expr.clone()).then_ignore(just(')')),
         ))
         .boxed();
@@ -244,6 +256,12 @@ pub fn expr_dependencies(expr: &Expr) -> HashSet<String> {
                 Expr::Number(_, _) => {
                     break;
                 }
+                Expr::Array(elems, _) => {
+                    for elem in elems {
+                        collect_path(elem, deps);
+                    }
+                    break;
+                }
                 Expr::Field(base, field, _) => {
                     current_path.push(field.clone());
                     node = base;
@@ -287,6 +305,7 @@ mod tests {
         StringTemplate(Vec<TestStringTemplateSegment>),
         Variable(&'static str),
         Number(&'static str),
+        Array(Vec<TestExpr>),
         Field(Box<TestExpr>, &'static str),
         FunctionCall {
             callee: Box<TestExpr>,
@@ -312,6 +331,12 @@ mod tests {
             (Expr::Number(n, _), TestExpr::Number(test_n)) => {
                 assert_eq!(n, test_n);
             }
+            (Expr::Array(elems, _), TestExpr::Array(test_elems)) => {
+                assert_eq!(elems.len(), test_elems.len());
+                for (elem, test_elem) in elems.iter().zip(test_elems) {
+                    assert_expr_matches(elem, test_elem);
+                }
+            }
             (Expr::Field(base, field, _), TestExpr::Field(test_base, test_field)) => {
                 assert_expr_matches(base, test_base);
                 assert_eq!(field, test_field);
@@ -561,6 +586,17 @@ mod tests {
         assert_expr_matches(&expr, &expected);
     }
 
+    #[test]
+    fn test_array_literal() {
+        let expr = parse("[1, \"two\", user.name]").unwrap();
+        let expected = TestExpr::Array(vec![
+            TestExpr::Number("1"),
+            TestExpr::StringTemplate(vec![TestStringTemplateSegment::Literal("two")]),
+            TestExpr::Field(Box::new(TestExpr::Variable("user")), "name"),
+        ]);
+        assert_expr_matches(&expr, &expected);
+    }
+
     #[test]
     fn test_parse_error() {
         assert!(parse("invalid{").is_err());
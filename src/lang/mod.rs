@@ -2,8 +2,10 @@ mod ast;
 mod ast_query;
 mod expr;
 mod parser;
+mod type_expr;
 
 pub use ast::*;
 pub use ast_query::*;
 pub use expr::*;
 pub use parser::*;
+pub use type_expr::*;
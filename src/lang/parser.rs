@@ -57,6 +57,22 @@ fn template_parser<'a>(source: SourceId) -> impl Parser<'a, &'a str, Vec<Node>,
             .labelled("identifier")
             .boxed();
 
+        // Attribute names may also contain ':' for directives like bind:value
+        let attr_name_with_span = any()
+            .filter(|c: &char| c.is_ascii_alphabetic() || *c == '_')
+            .then(
+                any()
+                    .filter(|c: &char| {
+                        c.is_ascii_alphanumeric() || *c == '_' || *c == '-' || *c == ':'
+                    })
+                    .repeated(),
+            )
+            .to_slice()
+            .map_with(move |name: &str, e| (name.to_string(), sourced_span(source, e.span())))
+            .padded()
+            .labelled("attribute name")
+            .boxed();
+
         // Expression parser for bindings
         let expr_parser = expr_parser(source);
 
@@ -90,6 +106,20 @@ fn template_parser<'a>(source: SourceId) -> impl Parser<'a, &'a str, Vec<Node>,
             .labelled("string")
             .boxed();
 
+        // Single-quoted raw text: no interpolation, so it may contain braces and double quotes
+        let raw_quoted_parser = just('\'')
+            .ignore_then(none_of('\'').repeated().to_slice())
+            .then_ignore(just('\''))
+            .map(|s: &str| {
+                AttrValue::Template(if s.is_empty() {
+                    vec![]
+                } else {
+                    vec![StringTemplateSegment::Literal(s.to_string())]
+                })
+            })
+            .labelled("string")
+            .boxed();
+
         // Unquoted binding parser
         let unquoted_binding_parser = binding_parser.clone().map(AttrValue::Expr).boxed();
 
@@ -97,14 +127,15 @@ fn template_parser<'a>(source: SourceId) -> impl Parser<'a, &'a str, Vec<Node>,
         let attr_value_parser = choice((
             // Quoted template: "hello {world}"
             quoted_template_parser,
+            // Raw text: '{ name: string }'
+            raw_quoted_parser,
             // Unquoted binding: {binding}
             unquoted_binding_parser,
         ))
         .boxed();
 
         // Attribute parser
-        let attribute_parser = identifier_with_span
-            .clone()
+        let attribute_parser = attr_name_with_span
             .then_ignore(just('=').padded())
             .then(attr_value_parser)
             .map_with(move |((name, name_span), value), e| SpannedAttribute {
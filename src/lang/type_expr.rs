@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use chumsky::prelude::*;
+use chumsky::span::SimpleSpan;
+
+use crate::error::Error;
+use crate::lang::{SourceId, Span};
+
+/// A type written in a template, e.g. in `<param name="user" type='{ name: string }'/>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeExpr {
+    /// `string`, `number`, `boolean`, `any`, or the name of a known type
+    Named(String, Span),
+    StringLiteral(String, Span),
+    /// A number literal, kept as written
+    NumberLiteral(String, Span),
+    Array(Box<TypeExpr>),
+    /// `Record<string, number>`, a dictionary from string keys to values
+    Record(Box<TypeExpr>),
+    /// `[number, string]`
+    Tuple(Vec<TypeExpr>),
+    Object(BTreeMap<String, TypeExpr>),
+    Function(Vec<TypeExpr>, Box<TypeExpr>),
+    Union(Vec<TypeExpr>, Span),
+}
+
+/// Parse a type written inside an attribute value starting at byte `offset` of the source,
+/// so that spans point into the original template.
+pub fn parse_type_expr(input: &str, source: SourceId, offset: usize) -> Result<TypeExpr, Error> {
+    type_expr_parser(source, offset)
+        .parse(input)
+        .into_result()
+        .map_err(|errors| {
+            let error = &errors[0];
+            let span = Span::new(
+                source,
+                offset + error.span().start..offset + error.span().end,
+            );
+            Error {
+                message: format!("Invalid type: {}", error.reason()),
+                main_span: span,
+                labels: vec![(span, "Parse error here".to_string())],
+            }
+        })
+}
+
+fn type_expr_parser<'a>(
+    source: SourceId,
+    offset: usize,
+) -> impl Parser<'a, &'a str, TypeExpr, extra::Err<Rich<'a, char>>> {
+    let sourced_span =
+        move |span: SimpleSpan| Span::new(source, offset + span.start..offset + span.end);
+
+    recursive(move |ty| {
+        let identifier = any()
+            .filter(|c: &char| c.is_ascii_alphabetic() || *c == '_')
+            .then(
+                any()
+                    .filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_')
+                    .repeated(),
+            )
+            .to_slice()
+            .map(|name: &str| name.to_string())
+            .labelled("identifier")
+            .boxed();
+
+        let named = identifier
+            .clone()
+            .map_with(move |name, e| TypeExpr::Named(name, sourced_span(e.span())))
+            .padded();
+
+        let identifier = identifier.padded();
+
+        let string_literal = just('"')
+            .ignore_then(none_of('"').repeated().to_slice())
+            .then_ignore(just('"'))
+            .map_with(move |value: &str, e| {
+                TypeExpr::StringLiteral(value.to_string(), sourced_span(e.span()))
+            })
+            .padded()
+            .labelled("string literal");
+
+        let number_literal = just('-')
+            .or_not()
+            .then(text::int(10))
+            .then(just('.').then(text::digits(10)).or_not())
+            .to_slice()
+            .map_with(move |value: &str, e| {
+                TypeExpr::NumberLiteral(value.to_string(), sourced_span(e.span()))
+            })
+            .padded()
+            .labelled("number literal");
+
+        // [number, string]
+        let tuple = ty
+            .clone()
+            .separated_by(just(',').padded())
+            .allow_trailing()
+            .collect::<Vec<TypeExpr>>()
+            .delimited_by(just('[').padded(), just(']').padded())
+            .map(TypeExpr::Tuple)
+            .labelled("tuple type");
+
+        // { name: string, age: number }
+        let object = identifier
+            .clone()
+            .then_ignore(just(':').padded())
+            .then(ty.clone())
+            .separated_by(one_of(",;").padded())
+            .allow_trailing()
+            .collect::<Vec<(String, TypeExpr)>>()
+            .delimited_by(just('{').padded(), just('}').padded())
+            .map(|fields| TypeExpr::Object(fields.into_iter().collect()))
+            .labelled("object type");
+
+        // (event: string, count: number) => void; parameter names are optional
+        let function = identifier
+            .clone()
+            .then_ignore(just(':').padded())
+            .or_not()
+            .ignore_then(ty.clone())
+            .separated_by(just(',').padded())
+            .allow_trailing()
+            .collect::<Vec<TypeExpr>>()
+            .delimited_by(just('(').padded(), just(')').padded())
+            .then_ignore(just("=>").padded())
+            .then(ty.clone())
+            .map(|(args, ret)| TypeExpr::Function(args, Box::new(ret)))
+            .labelled("function type");
+
+        // Record<string, number>; keys are always strings
+        let record = just("Record")
+            .padded()
+            .ignore_then(just('<').padded())
+            .ignore_then(just("string").padded())
+            .ignore_then(just(',').padded())
+            .ignore_then(ty.clone())
+            .then_ignore(just('>').padded())
+            .map(|value| TypeExpr::Record(Box::new(value)))
+            .labelled("record type");
+
+        let parenthesized = ty
+            .clone()
+            .delimited_by(just('(').padded(), just(')').padded());
+
+        let atom = choice((
+            object,
+            function,
+            parenthesized,
+            tuple,
+            string_literal,
+            number_literal,
+            record,
+            named,
+        ))
+        .boxed();
+
+        // string[][]
+        let array = atom
+            .then(just("[]").padded().repeated().count())
+            .map(|(mut ty, depth)| {
+                for _ in 0..depth {
+                    ty = TypeExpr::Array(Box::new(ty));
+                }
+                ty
+            })
+            .boxed();
+
+        array
+            .separated_by(just('|').padded())
+            .at_least(1)
+            .collect::<Vec<TypeExpr>>()
+            .map_with(move |mut members, e| {
+                if members.len() == 1 {
+                    members.remove(0)
+                } else {
+                    TypeExpr::Union(members, sourced_span(e.span()))
+                }
+            })
+    })
+    .padded()
+    .then_ignore(end())
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> TypeExpr {
+        parse_type_expr(input, 0, 0).unwrap()
+    }
+
+    fn named(ty: &TypeExpr) -> &str {
+        match ty {
+            TypeExpr::Named(name, _) => name,
+            other => panic!("expected a named type, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_named() {
+        assert_eq!(named(&parse(" string ")), "string");
+    }
+
+    #[test]
+    fn test_array() {
+        match parse("number[][]") {
+            TypeExpr::Array(inner) => match *inner {
+                TypeExpr::Array(elem) => assert_eq!(named(&elem), "number"),
+                other => panic!("expected nested array, got {:?}", other),
+            },
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_object() {
+        match parse("{ name: string; tags: string[], }") {
+            TypeExpr::Object(fields) => {
+                assert_eq!(fields.keys().collect::<Vec<_>>(), vec!["name", "tags"]);
+                assert_eq!(named(&fields["name"]), "string");
+                assert!(matches!(fields["tags"], TypeExpr::Array(_)));
+            }
+            other => panic!("expected object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_function() {
+        match parse("(id: number, string) => void") {
+            TypeExpr::Function(args, ret) => {
+                assert_eq!(args.len(), 2);
+                assert_eq!(named(&args[0]), "number");
+                assert_eq!(named(&args[1]), "string");
+                assert_eq!(named(&ret), "void");
+            }
+            other => panic!("expected function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parenthesized_array() {
+        match parse("(string)[]") {
+            TypeExpr::Array(elem) => assert_eq!(named(&elem), "string"),
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_string_literal_union() {
+        match parse(r#""idle" | "loading""#) {
+            TypeExpr::Union(members, _) => {
+                assert_eq!(members.len(), 2);
+                assert!(
+                    matches!(&members[0], TypeExpr::StringLiteral(value, _) if value == "idle")
+                );
+            }
+            other => panic!("expected union, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tuple_of_literals() {
+        match parse(r#"[1 | 2.5, "on" | true][]"#) {
+            TypeExpr::Array(elem) => match *elem {
+                TypeExpr::Tuple(elems) => {
+                    assert_eq!(elems.len(), 2);
+                    assert!(matches!(
+                        &elems[0],
+                        TypeExpr::Union(members, _)
+                            if matches!(&members[1], TypeExpr::NumberLiteral(value, _) if value == "2.5")
+                    ));
+                    assert!(matches!(
+                        &elems[1],
+                        TypeExpr::Union(members, _) if named(&members[1]) == "true"
+                    ));
+                }
+                other => panic!("expected tuple, got {:?}", other),
+            },
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        match parse("Record<string, number[]>") {
+            TypeExpr::Record(value) => match *value {
+                TypeExpr::Array(elem) => assert_eq!(named(&elem), "number"),
+                other => panic!("expected array value, got {:?}", other),
+            },
+            other => panic!("expected record, got {:?}", other),
+        }
+        assert_eq!(named(&parse("Records")), "Records");
+    }
+
+    #[test]
+    fn test_span_offset() {
+        let error = parse_type_expr("{ name string }", 0, 10).unwrap_err();
+        assert!(error.main_span.start >= 10);
+        match parse_type_expr("User", 0, 10).unwrap() {
+            TypeExpr::Named(_, span) => assert_eq!(span.start..span.end, 10..14),
+            other => panic!("expected named type, got {:?}", other),
+        }
+    }
+}
@@ -23,21 +23,50 @@ function lookup<V>(m: { [k: string]: V }, k: string, d: V) {
   return m[k] ?? d;
 }
 
-type ViewState<Input> = {
+export type ViewState<Input> = {
   root: any;
   update: (input: Input) => void;
+  // Release the view and its children; called before the view is removed
+  destroy: () => void;
 };
 export type View<Input> = (input: Input) => ViewState<Input>;
+// Opt-in hooks for <for>, <if> and <switch>, e.g. to run CSS animations
+export type Transition = {
+  // Called after an element is inserted by an update
+  enter: (element: Element) => void;
+  // Called before an element is removed; it stays in the document until done() is called
+  leave: (element: Element, done: () => void) => void;
+};
+// Placeholders such as empty branches are not elements, so they skip the hooks
+function enterNode(node: ChildNode, transition: Transition) {
+  if (node instanceof Element) {
+    transition.enter(node);
+  }
+}
+function leaveNode(node: ChildNode, transition: Transition) {
+  if (node instanceof Element) {
+    transition.leave(node, () => node.remove());
+  } else {
+    node.remove();
+  }
+}
+function replaceRoot(oldRoot: ChildNode, newRoot: ChildNode, transition: Transition) {
+  oldRoot.before(newRoot);
+  enterNode(newRoot, transition);
+  leaveNode(oldRoot, transition);
+}
 function updateForLoop<Input>({
   anchor,
   prevStates,
   nextInputs,
   subView,
+  transition,
 }: {
   anchor: Comment;
   prevStates: ViewState<Input>[];
   nextInputs: Input[];
   subView: View<Input>;
+  transition?: Transition;
 }) {
   const parent = anchor.parentNode!;
   let states = prevStates.slice();
@@ -45,7 +74,12 @@ function updateForLoop<Input>({
   // Remove extra items (from end, working backwards)
   while (states.length > nextInputs.length) {
     const removed = states.pop()!;
-    parent.removeChild(removed.root);
+    removed.destroy();
+    if (transition) {
+      leaveNode(removed.root, transition);
+    } else {
+      parent.removeChild(removed.root);
+    }
   }
 
   // Update existing items
@@ -57,11 +91,91 @@ function updateForLoop<Input>({
   for (let i = states.length; i < nextInputs.length; i++) {
     const state = subView(nextInputs[i]);
     parent.insertBefore(state.root, anchor);
+    if (transition) {
+      enterNode(state.root, transition);
+    }
     states.push(state);
   }
 
   return states;
 }
+// Used by <await>: shows the branch for the latest promise, ignoring superseded ones
+type AwaitBranch<Input> = {
+  view: View<any>;
+  input: (input: Input, value?: unknown) => any;
+};
+function awaitPromise<Input>(
+  promise: Promise<unknown>,
+  input: Input,
+  branches: {
+    pending?: AwaitBranch<Input>;
+    then?: AwaitBranch<Input>;
+    catch?: AwaitBranch<Input>;
+  }
+) {
+  let currentPromise: Promise<unknown> | undefined = promise;
+  let currentInput = input;
+  let settled: { branch: "then" | "catch"; value: unknown } | undefined;
+  const currentBranch = () => (settled ? branches[settled.branch] : branches.pending);
+  const build = (): ViewState<any> => {
+    const branch = currentBranch();
+    return branch
+      ? branch.view(branch.input(currentInput, settled?.value))
+      : { root: document.createComment("await-empty"), update: () => {}, destroy: () => {} };
+  };
+  let state = build();
+  const show = () => {
+    const next = build();
+    state.destroy();
+    state.root.replaceWith(next.root);
+    state = next;
+  };
+  const subscribe = (promise: Promise<unknown>) => {
+    const settle = (branch: "then" | "catch") => (value: unknown) => {
+      if (promise === currentPromise) {
+        settled = { branch, value };
+        show();
+      }
+    };
+    promise.then(settle("then"), settle("catch"));
+  };
+  subscribe(promise);
+  return {
+    get root() {
+      return state.root;
+    },
+    update(input: Input, promise: Promise<unknown>) {
+      currentInput = input;
+      if (promise !== currentPromise) {
+        currentPromise = promise;
+        settled = undefined;
+        show();
+        subscribe(promise);
+      } else {
+        const branch = currentBranch();
+        if (branch) {
+          state.update(branch.input(input, settled?.value));
+        }
+      }
+    },
+    destroy() {
+      currentPromise = undefined;
+      state.destroy();
+    },
+  };
+}
+// Used by <raw>: the content is inserted as HTML without escaping
+function parseRawHtml(html: string): Node[] {
+  const template = document.createElement("template");
+  template.innerHTML = html;
+  return Array.from(template.content.childNodes);
+}
+function replaceRawHtml(start: Comment, end: Comment, html: string) {
+  while (start.nextSibling && start.nextSibling !== end) {
+    start.nextSibling.remove();
+  }
+  start.after(...parseRawHtml(html));
+}
 export function run<Input>(
   view: View<Input>,
   buildComponent: (
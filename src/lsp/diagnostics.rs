@@ -39,9 +39,12 @@ pub fn collect(
 
     match load_ordered_views(entry_template_path, &mut resolver, &mut sources) {
         Ok(views) => {
-            if let Err(error) = compile::compile_views(&views) {
-                if let Some(diagnostic) = diagnostic_from_error(uri, snapshot, &error) {
-                    diagnostics.push(diagnostic);
+            if let Err(errors) = compile::compile_views(&views, &compile::CompileOptions::default())
+            {
+                for error in errors {
+                    if let Some(diagnostic) = diagnostic_from_error(uri, snapshot, &error) {
+                        diagnostics.push(diagnostic);
+                    }
                 }
             }
         }
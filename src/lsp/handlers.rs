@@ -295,7 +295,7 @@ impl<W: Write> LanguageServer<W> {
         };
 
         let result = load_ordered_views(entry_template_path, &mut resolver, &mut sources).ok()?;
-        let output = compile::compile_views(&result).ok()?;
+        let output = compile::compile_views(&result, &compile::CompileOptions::default()).ok()?;
         Some(output.view_types)
     }
 
@@ -431,7 +431,23 @@ fn top_level_fields(ty: TsType) -> Option<Vec<(String, TsType)>> {
 fn format_ts_type_pretty(ty: &TsType, indent: usize) -> String {
     match ty {
         TsType::SimpleType(s) => s.clone(),
-        TsType::Array(elem) => format!("{}[]", format_ts_type_pretty(elem, indent)),
+        TsType::Array(elem) => match elem.as_ref() {
+            TsType::Union(_) | TsType::Function(..) => {
+                format!("({})[]", format_ts_type_pretty(elem, indent))
+            }
+            _ => format!("{}[]", format_ts_type_pretty(elem, indent)),
+        },
+        TsType::Tuple(elems) => format!(
+            "[{}]",
+            elems
+                .iter()
+                .map(|elem| format_ts_type_pretty(elem, indent))
+                .join(", ")
+        ),
+        TsType::Promise(value) => format!("Promise<{}>", format_ts_type_pretty(value, indent)),
+        TsType::Record(value) => {
+            format!("Record<string, {}>", format_ts_type_pretty(value, indent))
+        }
         TsType::Function(params, ret) => {
             let params_rendered: Vec<String> = params
                 .iter()
@@ -450,6 +466,7 @@ fn format_ts_type_pretty(ty: &TsType, indent: usize) -> String {
             format!("View<{}>", inner)
         }
         TsType::Union(types) => format_union(types, indent),
+        TsType::Optional(inner) => format!("{} | undefined", format_ts_type_pretty(inner, indent)),
     }
 }
 
@@ -462,8 +479,13 @@ fn format_object(fields: &std::collections::BTreeMap<String, TsType>, indent: us
     let inner_indent = "  ".repeat(indent + 1);
     let mut lines = Vec::new();
     for (name, ty) in fields {
-        let rendered = format_ts_type_pretty(ty, indent + 1);
-        lines.push(format!("{}{}: {};", inner_indent, name, rendered));
+        let line = match ty {
+            TsType::Optional(inner) => {
+                format!("{}?: {}", name, format_ts_type_pretty(inner, indent + 1))
+            }
+            _ => format!("{}: {}", name, format_ts_type_pretty(ty, indent + 1)),
+        };
+        lines.push(format!("{}{};", inner_indent, line));
     }
 
     format!("{{\n{}\n{}}}", lines.join("\n"), indent_str)
@@ -1,3 +1,4 @@
+use crate::compile::CompileOptions;
 use crate::error::Error;
 use crate::template::{load_ordered_views, SourceMap, TemplatePath, TemplateResolver};
 use ariadne::{Color, Report, ReportKind, Source};
@@ -36,7 +37,11 @@ impl TemplateResolver for DiskResolver {
     }
 }
 
-fn compile_all(vg_files: &[PathBuf], quiet: bool) -> Result<(String, Vec<PathBuf>), ()> {
+fn compile_all(
+    vg_files: &[PathBuf],
+    options: &CompileOptions,
+    quiet: bool,
+) -> Result<(String, Vec<PathBuf>), ()> {
     if !quiet {
         eprintln!("Found {} .vg files.", vg_files.len());
     }
@@ -65,7 +70,7 @@ fn compile_all(vg_files: &[PathBuf], quiet: bool) -> Result<(String, Vec<PathBuf
         }
     }
 
-    match compile::compile_views(&ordered_views) {
+    match compile::compile_views(&ordered_views, options) {
         Ok(output) => {
             let watched_paths = sources
                 .iter()
@@ -73,8 +78,10 @@ fn compile_all(vg_files: &[PathBuf], quiet: bool) -> Result<(String, Vec<PathBuf
                 .collect();
             Ok((output.code, watched_paths))
         }
-        Err(error) => {
-            report_error(&sources, &error);
+        Err(errors) => {
+            for error in errors {
+                report_error(&sources, &error);
+            }
             Err(())
         }
     }
@@ -148,6 +155,18 @@ fn main() {
                 .help("Watch input .vg files for changes and recompile automatically")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("Reject views whose input types are not fully known")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("readonly")
+                .long("readonly")
+                .help("Emit readonly arrays and fields in input types")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("quiet")
                 .short('q')
@@ -173,6 +192,10 @@ fn main() {
     let output_file = matches.get_one::<String>("output");
     let watch = matches.get_flag("watch");
     let quiet = matches.get_flag("quiet");
+    let options = CompileOptions {
+        strict: matches.get_flag("strict"),
+        readonly: matches.get_flag("readonly"),
+    };
 
     if !quiet {
         eprintln!("Input file(s): {:?}", input_files);
@@ -200,7 +223,7 @@ fn main() {
     }
 
     if !watch {
-        match compile_all(&vg_files, quiet) {
+        match compile_all(&vg_files, &options, quiet) {
             Ok((output, _deps)) => {
                 write_output(output_file.map(|s| s.as_str()), &output);
             }
@@ -217,7 +240,7 @@ fn main() {
 
     // Initial build (do not exit on error, continue watching)
     let mut watched_paths = vg_files.clone();
-    match compile_all(&vg_files, quiet) {
+    match compile_all(&vg_files, &options, quiet) {
         Ok((output, deps)) => {
             write_output(output_file.map(|s| s.as_str()), &output);
             watched_paths = deps;
@@ -252,7 +275,7 @@ fn main() {
 
         if changed {
             eprintln!("Change detected. Recompiling...");
-            match compile_all(&vg_files, quiet) {
+            match compile_all(&vg_files, &options, quiet) {
                 Ok((output, deps)) => {
                     write_output(output_file.map(|s| s.as_str()), &output);
                     eprintln!("Rebuild complete.");
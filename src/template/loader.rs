@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::graph::{cycle_from_stack, topo_sort};
+use crate::graph::{cycle_from_stack, strongly_connected_components, topo_sort};
 use crate::lang::{parse_template, Span};
 use crate::template::module::{TemplateModule, ViewStub};
 use crate::template::path::normalize_path;
@@ -116,12 +116,31 @@ pub fn load_ordered_views<R: TemplateResolver>(
         }
     }
 
+    // Views that call each other are typed through their declared parameters, so calls
+    // within a cycle don't constrain the order
+    let mut recursive_views = HashSet::new();
+    for component in strongly_connected_components(&view_dependencies) {
+        let is_cycle =
+            component.len() > 1 || view_dependencies[&component[0]].contains(&component[0]);
+        if !is_cycle {
+            continue;
+        }
+        for name in &component {
+            if let Some(deps) = view_dependencies.get_mut(name) {
+                deps.retain(|dep| !component.contains(dep));
+            }
+        }
+        recursive_views.extend(component);
+    }
+
     let order = topologically_sort_views(&view_dependencies, |name| view_spans.get(name).cloned())?;
 
     let mut ordered_views = Vec::with_capacity(order.len());
     for view_name in order {
         if let Some((module_idx, view_idx)) = view_lookup.get(&view_name) {
-            ordered_views.push(modules_vec[*module_idx].views[*view_idx].clone());
+            let mut view = modules_vec[*module_idx].views[*view_idx].clone();
+            view.recursive = recursive_views.contains(&view_name);
+            ordered_views.push(view);
         }
     }
 
@@ -5,7 +5,7 @@ pub mod resolver;
 pub mod source_map;
 
 pub use loader::load_ordered_views;
-pub use module::ViewStub;
+pub use module::{ViewParam, ViewStub};
 pub use path::normalize_path;
 pub use resolver::TemplateResolver;
 pub use source_map::{SourceMap, TemplatePath};
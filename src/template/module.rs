@@ -1,5 +1,8 @@
 use crate::error::Error;
-use crate::lang::{expect_element, find_literal_attr, validate_single_child, Node, Span};
+use crate::lang::{
+    expect_element, find_literal_attr, match_element_name, parse_type_expr, validate_single_child,
+    AttrValue, Expr, Node, Span, SpannedAttribute, StringTemplateSegment, TypeExpr,
+};
 use crate::template::source_map::TemplatePath;
 
 #[derive(Debug, Clone)]
@@ -15,6 +18,37 @@ pub struct ViewStub {
     pub view_span: Span,
     pub root: Node,
     pub component_refs: Vec<ComponentRef>,
+    /// Declared inputs; when non-empty, the view may not use any other inputs
+    pub params: Vec<ViewParam>,
+    /// Type parameters declared with `generics="T, U"`, usable in parameter declarations
+    pub generics: Vec<(String, Span)>,
+    /// Set with `strict="true"` or `strict="false"`; overrides the compilation's setting
+    pub strict: Option<bool>,
+    /// Set with `readonly="true"` or `readonly="false"`; overrides the compilation's setting
+    pub readonly: Option<bool>,
+    /// Types imported by the view's template, usable in its parameter declarations
+    pub type_imports: Vec<TypeImport>,
+    /// Whether the view calls itself, directly or through other views; set by the loader
+    pub recursive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeImport {
+    pub names: Vec<(String, Span)>,
+    pub from: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ViewParam {
+    pub name: String,
+    pub name_span: Span,
+    /// `None` declares the input without constraining its type
+    pub ty: Option<TypeExpr>,
+    /// A string or number literal; makes the input optional for callers
+    pub default: Option<Expr>,
+    /// Name for the input's object type, or for the objects in it, set with `alias="Todo"`
+    pub alias: Option<(String, Span)>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +68,7 @@ impl TemplateModule {
     pub fn from_nodes(path: TemplatePath, nodes: Vec<Node>) -> Result<Self, Error> {
         let mut requires = Vec::new();
         let mut views = Vec::new();
+        let mut type_imports = Vec::new();
 
         for node in &nodes {
             match node {
@@ -43,10 +78,13 @@ impl TemplateModule {
                 Node::Element { name, .. } if name == "view" => {
                     views.push(parse_view(node)?);
                 }
+                Node::Element { name, .. } if name == "import-type" => {
+                    type_imports.push(parse_type_import(node)?);
+                }
                 _ => {
                     let span = *node.span();
                     return Err(Error {
-                        message: "Only <require> and <view> elements are allowed at the top level."
+                        message: "Only <require>, <import-type> and <view> elements are allowed at the top level."
                             .to_string(),
                         main_span: span,
                         labels: vec![(
@@ -58,6 +96,10 @@ impl TemplateModule {
             }
         }
 
+        for view in &mut views {
+            view.type_imports = type_imports.clone();
+        }
+
         Ok(Self {
             path,
             requires,
@@ -101,6 +143,76 @@ fn parse_require(node: &Node) -> Result<RequiredTemplate, Error> {
     })
 }
 
+fn parse_type_import(node: &Node) -> Result<TypeImport, Error> {
+    let (attrs, children, span) = expect_element(node, "import-type")?;
+
+    if !children.is_empty() {
+        return Err(Error {
+            message: "<import-type> must not have children.".to_string(),
+            main_span: *span,
+            labels: vec![(
+                *span,
+                "Remove nested content; <import-type> is self-closing.".to_string(),
+            )],
+        });
+    }
+
+    for attr in attrs {
+        if attr.name != "names" && attr.name != "from" {
+            return Err(Error {
+                message: format!("Unexpected '{}' attribute on <import-type>.", attr.name),
+                main_span: attr.span,
+                labels: vec![(
+                    attr.span,
+                    "Only the 'names' and 'from' attributes are supported.".to_string(),
+                )],
+            });
+        }
+    }
+
+    let (names_text, names_span) = find_literal_attr(attrs, "names", span)?;
+    let (from, _) = find_literal_attr(attrs, "from", span)?;
+
+    let names = parse_name_list(&names_text, names_span)?;
+
+    Ok(TypeImport { names, from })
+}
+
+/// Split a comma-separated list of type names, such as `names="Todo, Filter"`.
+fn parse_name_list(text: &str, attr_span: Span) -> Result<Vec<(String, Span)>, Error> {
+    // The value sits just before the closing quote of the attribute
+    let mut offset = attr_span.end - 1 - text.len();
+    let mut names = Vec::new();
+    for part in text.split(',') {
+        let name = part.trim();
+        let start = offset + part.len() - part.trim_start().len();
+        let name_span = Span {
+            start,
+            end: start + name.len(),
+            context: attr_span.context,
+        };
+        offset += part.len() + 1;
+
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Error {
+                message: format!("'{}' is not a valid type name.", name),
+                main_span: name_span,
+                labels: vec![(
+                    name_span,
+                    "Expected a comma-separated list of type names".to_string(),
+                )],
+            });
+        }
+        names.push((name.to_string(), name_span));
+    }
+    Ok(names)
+}
+
 fn parse_view(node: &Node) -> Result<ViewStub, Error> {
     let (attrs, children, span) = expect_element(node, "view")?;
     let (name, name_span) = find_literal_attr(attrs, "name", span)?;
@@ -116,8 +228,50 @@ fn parse_view(node: &Node) -> Result<ViewStub, Error> {
         });
     }
 
-    validate_single_child(span, children)?;
-    let root = children[0].clone();
+    let generics = match attrs.iter().find(|attr| attr.name == "generics") {
+        Some(_) => {
+            let (text, attr_span) = find_literal_attr(attrs, "generics", span)?;
+            let generics = parse_name_list(&text, attr_span)?;
+            for (i, (name, name_span)) in generics.iter().enumerate() {
+                if generics[..i].iter().any(|(other, _)| other == name) {
+                    return Err(Error {
+                        message: format!("Type parameter '{}' is declared more than once.", name),
+                        main_span: *name_span,
+                        labels: vec![(*name_span, "Duplicate declaration".to_string())],
+                    });
+                }
+            }
+            generics
+        }
+        None => Vec::new(),
+    };
+
+    let strict = parse_bool_attr(attrs, "strict", span)?;
+    let readonly = parse_bool_attr(attrs, "readonly", span)?;
+
+    let (param_nodes, body): (Vec<Node>, Vec<Node>) = children
+        .iter()
+        .cloned()
+        .partition(|child| match_element_name(child, "param"));
+
+    let mut params: Vec<ViewParam> = Vec::new();
+    for node in &param_nodes {
+        let param = parse_param(node)?;
+        if let Some(existing) = params.iter().find(|p| p.name == param.name) {
+            return Err(Error {
+                message: format!("Parameter '{}' is declared more than once.", param.name),
+                main_span: param.name_span,
+                labels: vec![
+                    (param.name_span, "Duplicate declaration".to_string()),
+                    (existing.name_span, "First declared here".to_string()),
+                ],
+            });
+        }
+        params.push(param);
+    }
+
+    validate_single_child(span, &body)?;
+    let root = body[0].clone();
 
     if matches!(root, Node::Expr(_)) {
         return Err(Error {
@@ -139,6 +293,128 @@ fn parse_view(node: &Node) -> Result<ViewStub, Error> {
         view_span: *span,
         root,
         component_refs,
+        params,
+        generics,
+        strict,
+        readonly,
+        type_imports: Vec::new(),
+        recursive: false,
+    })
+}
+
+/// Read an optional `"true"` or `"false"` attribute.
+fn parse_bool_attr(
+    attrs: &[SpannedAttribute],
+    name: &str,
+    element_span: &Span,
+) -> Result<Option<bool>, Error> {
+    if !attrs.iter().any(|attr| attr.name == name) {
+        return Ok(None);
+    }
+    let (text, attr_span) = find_literal_attr(attrs, name, element_span)?;
+    match text.as_str() {
+        "true" => Ok(Some(true)),
+        "false" => Ok(Some(false)),
+        _ => Err(Error {
+            message: format!("Invalid value '{}' for '{}'.", text, name),
+            main_span: attr_span,
+            labels: vec![(attr_span, "Expected \"true\" or \"false\"".to_string())],
+        }),
+    }
+}
+
+fn parse_param(node: &Node) -> Result<ViewParam, Error> {
+    let (attrs, children, span) = expect_element(node, "param")?;
+
+    if !children.is_empty() {
+        return Err(Error {
+            message: "<param> must not have children.".to_string(),
+            main_span: *span,
+            labels: vec![(
+                *span,
+                "Remove nested content; <param> is self-closing.".to_string(),
+            )],
+        });
+    }
+
+    for attr in attrs {
+        if !["name", "type", "default", "alias"].contains(&attr.name.as_str()) {
+            return Err(Error {
+                message: format!("Unexpected '{}' attribute on <param>.", attr.name),
+                main_span: attr.span,
+                labels: vec![(
+                    attr.span,
+                    "Only the 'name', 'type', 'default' and 'alias' attributes are supported."
+                        .to_string(),
+                )],
+            });
+        }
+    }
+
+    let (name, name_span) = find_literal_attr(attrs, "name", span)?;
+
+    let ty = if attrs.iter().any(|attr| attr.name == "type") {
+        let (text, attr_span) = find_literal_attr(attrs, "type", span)?;
+        // The value sits just before the closing quote of the attribute
+        let offset = attr_span.end - 1 - text.len();
+        Some(parse_type_expr(&text, attr_span.context, offset)?)
+    } else {
+        None
+    };
+
+    let alias = match attrs.iter().find(|attr| attr.name == "alias") {
+        Some(_) => {
+            let (text, attr_span) = find_literal_attr(attrs, "alias", span)?;
+            let mut names = parse_name_list(&text, attr_span)?;
+            if names.len() != 1 {
+                return Err(Error {
+                    message: "'alias' takes a single type name.".to_string(),
+                    main_span: attr_span,
+                    labels: vec![(attr_span, "Remove the other names".to_string())],
+                });
+            }
+            names.pop()
+        }
+        None => None,
+    };
+
+    let default = attrs
+        .iter()
+        .find(|attr| attr.name == "default")
+        .map(|attr| {
+            let expr = match &attr.value {
+                AttrValue::Template(segments) => Expr::StringTemplate(segments.clone(), attr.span),
+                AttrValue::Expr(expr) => expr.clone(),
+            };
+            let is_literal = match &expr {
+                Expr::Number(..) => true,
+                Expr::StringTemplate(segments, _) => segments
+                    .iter()
+                    .all(|segment| matches!(segment, StringTemplateSegment::Literal(_))),
+                _ => false,
+            };
+            if is_literal {
+                Ok(expr)
+            } else {
+                Err(Error {
+                    message: "Parameter defaults must be a string or number literal.".to_string(),
+                    main_span: attr.span,
+                    labels: vec![(
+                        attr.span,
+                        "Use e.g. default=\"primary\" or default={0}".to_string(),
+                    )],
+                })
+            }
+        })
+        .transpose()?;
+
+    Ok(ViewParam {
+        name,
+        name_span,
+        ty,
+        default,
+        alias,
+        span: *span,
     })
 }
 
@@ -1,5 +1,5 @@
 use itertools::Itertools;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 
 use crate::ts_util::render_key;
@@ -13,81 +13,520 @@ pub enum TsType {
     SimpleType(String),
     Object(BTreeMap<String, TsType>),
     Array(Box<TsType>),
+    Tuple(Vec<TsType>),
+    Promise(Box<TsType>),
+    /// `Record<string, T>`
+    Record(Box<TsType>),
     Function(Vec<TsType>, Box<TsType>),
     Union(Vec<TsType>),
     View(BTreeMap<String, TsType>),
+    /// An object field the caller may omit, rendered as `key?: T`
+    Optional(Box<TsType>),
 }
 
+/// `{:#}` renders readonly arrays, dictionaries and fields. Function parameters and view
+/// inputs are readonly too, since the view passes its readonly inputs to them.
 impl fmt::Display for TsType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn render_object(obj: &BTreeMap<String, TsType>) -> String {
+        fn show(ty: &TsType, readonly: bool) -> String {
+            if readonly {
+                format!("{:#}", ty)
+            } else {
+                ty.to_string()
+            }
+        }
+
+        fn render_object(obj: &BTreeMap<String, TsType>, readonly: bool) -> String {
             if obj.is_empty() {
                 "{}".to_string()
             } else {
+                let modifier = if readonly { "readonly " } else { "" };
                 let field_strings: Vec<String> = obj
                     .iter()
-                    .map(|(key, value)| format!("{}: {}", render_key(key), value))
+                    .map(|(key, value)| match value {
+                        TsType::Optional(inner) => format!(
+                            "{}{}?: {}",
+                            modifier,
+                            render_key(key),
+                            show(inner, readonly)
+                        ),
+                        _ => format!("{}{}: {}", modifier, render_key(key), show(value, readonly)),
+                    })
                     .collect();
                 format!("{{ {} }}", field_strings.join(", "))
             }
         }
 
+        // Unions and functions need parentheses before `[]`, as do arrays and tuples that
+        // start with `readonly`
+        fn show_element(ty: &TsType, readonly: bool) -> String {
+            match ty {
+                TsType::Union(_) | TsType::Function(..) => format!("({})", show(ty, readonly)),
+                TsType::Array(_) | TsType::Tuple(_) if readonly => {
+                    format!("({})", show(ty, readonly))
+                }
+                _ => show(ty, readonly),
+            }
+        }
+
+        let readonly = f.alternate();
         match self {
             TsType::SimpleType(s) => write!(f, "{}", s),
-            TsType::Object(fields) => write!(f, "{}", render_object(fields)),
-            TsType::Array(element_type) => write!(f, "{}[]", element_type),
+            TsType::Object(fields) => write!(f, "{}", render_object(fields, readonly)),
+            TsType::Array(element_type) if readonly => {
+                write!(f, "readonly {}[]", show_element(element_type, readonly))
+            }
+            TsType::Array(element_type) => write!(f, "{}[]", show_element(element_type, false)),
+            TsType::Tuple(elements) => {
+                let parts = elements.iter().map(|t| show(t, readonly)).join(", ");
+                if readonly {
+                    write!(f, "readonly [{}]", parts)
+                } else {
+                    write!(f, "[{}]", parts)
+                }
+            }
+            TsType::Promise(value_type) => write!(f, "Promise<{}>", show(value_type, readonly)),
+            TsType::Record(value_type) if readonly => write!(
+                f,
+                "Readonly<Record<string, {}>>",
+                show(value_type, readonly)
+            ),
+            TsType::Record(value_type) => write!(f, "Record<string, {}>", value_type),
             TsType::Function(params, return_type) => {
                 let param_strings: Vec<String> = params
                     .iter()
                     .enumerate()
-                    .map(|(i, p)| format!("v{}: {}", i, p))
+                    .map(|(i, p)| format!("v{}: {}", i, show(p, readonly)))
                     .collect();
-                write!(f, "({}) => {}", param_strings.join(", "), return_type)
+                write!(
+                    f,
+                    "({}) => {}",
+                    param_strings.join(", "),
+                    show(return_type, readonly)
+                )
             }
             TsType::Union(types) => {
-                let parts: Vec<String> = types.iter().map(|t| t.to_string()).collect();
+                let parts: Vec<String> = types.iter().map(|t| show(t, readonly)).collect();
                 write!(f, "{}", parts.join(" | "))
             }
-            TsType::View(fields) => write!(f, "View<{}>", render_object(fields)),
+            TsType::View(fields) => write!(f, "View<{}>", render_object(fields, readonly)),
+            TsType::Optional(inner) => write!(f, "{} | undefined", show(inner, readonly)),
         }
     }
 }
 
-/// Convert an environment (variable name -> type mapping) to a TsType::Object
-pub fn env_to_ts_type(env: &Env) -> TsType {
-    let mut fields = BTreeMap::new();
-    for (name, ty) in env {
-        let canonical = canonical_type(ty);
-        let ts_type = type_to_ts_type(&canonical);
-        fields.insert(name.clone(), ts_type);
+/// A named type emitted alongside a view's input type, e.g. `TodoInputTodosItem<T>`
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    pub name: String,
+    /// The view's type parameters that the aliased type uses
+    pub params: Vec<String>,
+    pub ty: TsType,
+}
+
+impl TypeAlias {
+    /// The alias as other types refer to it, with its type parameters
+    pub fn reference(&self) -> String {
+        if self.params.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}<{}>", self.name, self.params.join(", "))
+        }
+    }
+}
+
+/// Name the nested objects of a view's input type after their path from the input, so that
+/// `todos: { ... }[]` in `TodoInput` becomes `todos: TodoInputTodosItem[]`. `overrides` maps
+/// an input field to the name of its object, or of the objects in it, and `taken` holds the
+/// names already in scope. Objects in function types stay inline unless they match an alias,
+/// as do the variants of discriminated unions.
+pub fn name_nested_types(
+    input: &TsType,
+    input_name: &str,
+    generics: &[String],
+    overrides: &BTreeMap<String, String>,
+    taken: &mut HashSet<String>,
+) -> (TsType, Vec<TypeAlias>) {
+    let TsType::Object(fields) = input else {
+        return (input.clone(), Vec::new());
+    };
+    let mut aliaser = Aliaser {
+        generics,
+        taken,
+        aliases: Vec::new(),
+    };
+    let fields: BTreeMap<String, TsType> = fields
+        .iter()
+        .map(|(key, ty)| {
+            let ty = match overrides.get(key) {
+                Some(name) => aliaser.name_objects(ty, name, true),
+                None => {
+                    aliaser.name_objects(ty, &format!("{}{}", input_name, pascal_case(key)), false)
+                }
+            };
+            (key.clone(), ty)
+        })
+        .collect();
+
+    let mut aliases = aliaser.aliases;
+    let known: HashMap<String, String> = aliases
+        .iter()
+        .map(|alias| (alias.ty.to_string(), alias.reference()))
+        .collect();
+    for alias in &mut aliases {
+        alias.ty = reuse_aliases(&alias.ty, &known, false);
+    }
+    let fields = fields
+        .into_iter()
+        .map(|(key, ty)| (key, reuse_aliases(&ty, &known, false)))
+        .collect();
+    (TsType::Object(fields), aliases)
+}
+
+struct Aliaser<'a> {
+    generics: &'a [String],
+    taken: &'a mut HashSet<String>,
+    aliases: Vec<TypeAlias>,
+}
+
+impl Aliaser<'_> {
+    /// Replace the objects in `ty` with aliases named after `name`; an `exact` name is used
+    /// as is for the first objects reached, without the path suffixes.
+    fn name_objects(&mut self, ty: &TsType, name: &str, exact: bool) -> TsType {
+        let child_name = |suffix: &str| {
+            if exact {
+                name.to_string()
+            } else {
+                format!("{}{}", name, suffix)
+            }
+        };
+        match ty {
+            TsType::Object(fields) if !fields.is_empty() => {
+                let name = if exact {
+                    name.to_string()
+                } else {
+                    self.unique(name)
+                };
+                self.taken.insert(name.clone());
+                // Reserve the alias's place so that it comes before the objects inside it
+                let index = self.aliases.len();
+                let fields = fields
+                    .iter()
+                    .map(|(key, ty)| {
+                        let field_name = format!("{}{}", name, pascal_case(key));
+                        (key.clone(), self.name_objects(ty, &field_name, false))
+                    })
+                    .collect();
+                let params = self
+                    .generics
+                    .iter()
+                    .filter(|generic| mentions(ty, generic))
+                    .cloned()
+                    .collect();
+                let alias = TypeAlias {
+                    name,
+                    params,
+                    ty: TsType::Object(fields),
+                };
+                let reference = alias.reference();
+                self.aliases.insert(index, alias);
+                TsType::SimpleType(reference)
+            }
+            TsType::Array(elem) => TsType::Array(Box::new(self.name_objects(
+                elem,
+                &child_name("Item"),
+                exact,
+            ))),
+            TsType::Tuple(elements) => TsType::Tuple(
+                elements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, elem)| self.name_objects(elem, &format!("{}Item{}", name, i), false))
+                    .collect(),
+            ),
+            TsType::Promise(value) => TsType::Promise(Box::new(self.name_objects(
+                value,
+                &child_name("Value"),
+                exact,
+            ))),
+            TsType::Record(value) => TsType::Record(Box::new(self.name_objects(
+                value,
+                &child_name("Value"),
+                exact,
+            ))),
+            TsType::Optional(inner) => {
+                TsType::Optional(Box::new(self.name_objects(inner, name, exact)))
+            }
+            _ => ty.clone(),
+        }
+    }
+
+    fn unique(&self, name: &str) -> String {
+        if !self.taken.contains(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|i| format!("{}{}", name, i))
+            .find(|candidate| !self.taken.contains(candidate))
+            .expect("candidate names are unbounded")
+    }
+}
+
+/// Replace the objects inside function types that have the same shape as an alias
+fn reuse_aliases(ty: &TsType, known: &HashMap<String, String>, in_function: bool) -> TsType {
+    let reuse = |ty: &TsType| Box::new(reuse_aliases(ty, known, in_function));
+    match ty {
+        TsType::SimpleType(_) => ty.clone(),
+        TsType::Object(fields) => {
+            let object = TsType::Object(
+                fields
+                    .iter()
+                    .map(|(key, ty)| (key.clone(), reuse_aliases(ty, known, in_function)))
+                    .collect(),
+            );
+            match known.get(&object.to_string()) {
+                Some(reference) if in_function => TsType::SimpleType(reference.clone()),
+                _ => object,
+            }
+        }
+        TsType::Array(elem) => TsType::Array(reuse(elem)),
+        TsType::Tuple(elements) => TsType::Tuple(
+            elements
+                .iter()
+                .map(|elem| reuse_aliases(elem, known, in_function))
+                .collect(),
+        ),
+        TsType::Promise(value) => TsType::Promise(reuse(value)),
+        TsType::Record(value) => TsType::Record(reuse(value)),
+        TsType::Optional(inner) => TsType::Optional(reuse(inner)),
+        TsType::Function(params, ret) => TsType::Function(
+            params
+                .iter()
+                .map(|param| reuse_aliases(param, known, true))
+                .collect(),
+            Box::new(reuse_aliases(ret, known, true)),
+        ),
+        TsType::Union(variants) => TsType::Union(
+            variants
+                .iter()
+                .map(|variant| reuse_aliases(variant, known, in_function))
+                .collect(),
+        ),
+        TsType::View(fields) => TsType::View(
+            fields
+                .iter()
+                .map(|(key, ty)| (key.clone(), reuse_aliases(ty, known, in_function)))
+                .collect(),
+        ),
+    }
+}
+
+/// Whether a type refers to the type named `name`
+fn mentions(ty: &TsType, name: &str) -> bool {
+    match ty {
+        TsType::SimpleType(simple) => simple == name,
+        TsType::Object(fields) | TsType::View(fields) => {
+            fields.values().any(|ty| mentions(ty, name))
+        }
+        TsType::Array(inner)
+        | TsType::Promise(inner)
+        | TsType::Record(inner)
+        | TsType::Optional(inner) => mentions(inner, name),
+        TsType::Function(params, ret) => {
+            params.iter().any(|param| mentions(param, name)) || mentions(ret, name)
+        }
+        TsType::Union(variants) | TsType::Tuple(variants) => {
+            variants.iter().any(|variant| mentions(variant, name))
+        }
+    }
+}
+
+/// `on-pick` and `onPick` both become `OnPick`
+fn pascal_case(key: &str) -> String {
+    key.split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Convert an environment to a TsType::Object, naming the type variables that TypeScript
+/// should see as generics: the declared ones, then any other variable that occurs more than
+/// once. A variable that occurs only once says nothing about the caller, so it stays `any`.
+pub fn env_to_generic_ts_type(env: &Env, declared: &[(String, Type)]) -> (Vec<String>, TsType) {
+    let fields: Vec<(&String, Type)> = env
+        .into_iter()
+        .sorted_by_key(|(name, _)| *name)
+        .map(|(name, ty)| (name, canonical_type(ty)))
+        .collect();
+
+    let mut names: HashMap<usize, String> = HashMap::new();
+    let mut generics = Vec::new();
+    for (name, ty) in declared {
+        if let Type::Var(point) = canonical_type(ty) {
+            names.insert(point.id(), name.clone());
+        }
+        generics.push(name.clone());
+    }
+
+    let mut occurrences = Vec::new();
+    for (_, ty) in &fields {
+        collect_vars(ty, &mut occurrences);
+    }
+    let counts = occurrences.iter().counts();
+    let mut candidates = ["T", "U", "V", "W"]
+        .into_iter()
+        .map(str::to_string)
+        .chain((1..).map(|i| format!("T{}", i)));
+    for id in occurrences.iter().unique() {
+        if counts[id] < 2 || names.contains_key(id) {
+            continue;
+        }
+        let name = candidates
+            .find(|candidate| !generics.contains(candidate))
+            .expect("candidate names are unbounded");
+        names.insert(*id, name.clone());
+        generics.push(name);
+    }
+
+    let fields = fields
+        .into_iter()
+        .map(|(name, ty)| (name.clone(), convert(&ty, &names)))
+        .collect();
+    (generics, TsType::Object(fields))
+}
+
+/// Ids of the unbound variables in a canonical type, once per occurrence, in order
+fn collect_vars(ty: &Type, ids: &mut Vec<usize>) {
+    match ty {
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => {}
+        Type::Fun(params, ret) => {
+            for param in params {
+                collect_vars(param, ids);
+            }
+            collect_vars(ret, ids);
+        }
+        Type::Array(inner) | Type::Promise(inner) | Type::Dict(inner) => collect_vars(inner, ids),
+        Type::Tuple(elems) => {
+            for elem in elems {
+                collect_vars(elem, ids);
+            }
+        }
+        Type::Var(point) => ids.push(point.id()),
+        Type::Record(row) => collect_row_vars(row, ids),
+        Type::DiscriminatedUnion(_, map) => {
+            for row in map.values() {
+                collect_row_vars(row, ids);
+            }
+        }
+        Type::View(attributes) => {
+            for ty in attributes.values() {
+                collect_vars(&canonical_type(ty), ids);
+            }
+        }
+    }
+}
+
+fn collect_row_vars(row: &uf::Point<RowDescriptor>, ids: &mut Vec<usize>) {
+    if let RowDescriptor::RowExtend(fields, rest) = uf::get(row) {
+        for ty in fields.values() {
+            collect_vars(&canonical_type(ty), ids);
+        }
+        collect_row_vars(&rest, ids);
+    }
+}
+
+/// Whether a canonical type is fully known: every variable is one of `allowed` and every
+/// record is closed. Open records and other variables come from inference alone.
+pub fn is_fully_typed(ty: &Type, allowed: &HashSet<usize>) -> bool {
+    match ty {
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => true,
+        Type::Fun(params, ret) => {
+            params.iter().all(|param| is_fully_typed(param, allowed))
+                && is_fully_typed(ret, allowed)
+        }
+        Type::Array(inner) | Type::Promise(inner) | Type::Dict(inner) => {
+            is_fully_typed(inner, allowed)
+        }
+        Type::Tuple(elems) => elems.iter().all(|elem| is_fully_typed(elem, allowed)),
+        Type::Var(point) => allowed.contains(&point.id()),
+        Type::Record(row) => is_row_fully_typed(row, allowed),
+        Type::DiscriminatedUnion(_, map) => {
+            map.values().all(|row| is_row_fully_typed(row, allowed))
+        }
+        Type::View(attributes) => attributes
+            .values()
+            .all(|ty| is_fully_typed(&canonical_type(ty), allowed)),
+    }
+}
+
+fn is_row_fully_typed(row: &uf::Point<RowDescriptor>, allowed: &HashSet<usize>) -> bool {
+    match uf::get(row) {
+        RowDescriptor::RowExtend(fields, rest) => {
+            fields
+                .values()
+                .all(|ty| is_fully_typed(&canonical_type(ty), allowed))
+                && is_row_fully_typed(&rest, allowed)
+        }
+        RowDescriptor::RowFlex(_) => false,
+        RowDescriptor::RowEmpty => true,
     }
-    TsType::Object(fields)
 }
 
 /// Convert a canonical Type to TsType
 /// The input type should already be canonical (all type variables resolved)
 pub fn type_to_ts_type(ty: &Type) -> TsType {
+    convert(ty, &HashMap::new())
+}
+
+/// Convert a canonical Type to TsType, rendering the variables in `names` as generics
+fn convert(ty: &Type, names: &HashMap<usize, String>) -> TsType {
     match ty {
-        Type::Prim(name) => TsType::SimpleType(name.clone()),
+        Type::Prim(name) | Type::Named(name) => TsType::SimpleType(name.clone()),
+        Type::Literal(literals) => {
+            let mut members = literals
+                .iter()
+                .map(|literal| TsType::SimpleType(literal.to_string()))
+                .collect_vec();
+            if members.len() == 1 {
+                members.remove(0)
+            } else {
+                TsType::Union(members)
+            }
+        }
+        Type::Error => TsType::SimpleType("any".to_string()),
         Type::Fun(params, ret) => {
-            let param_types = params.iter().map(type_to_ts_type).collect_vec();
-            let ret_type = Box::new(type_to_ts_type(ret));
+            let param_types = params
+                .iter()
+                .map(|param| convert(param, names))
+                .collect_vec();
+            let ret_type = Box::new(convert(ret, names));
             TsType::Function(param_types, ret_type)
         }
-        Type::Array(elem) => TsType::Array(Box::new(type_to_ts_type(elem))),
-        Type::Var(_) => {
-            // If we get an unbound variable after canonicalization, treat it as 'any'
-            TsType::SimpleType("any".to_string())
+        Type::Array(elem) => TsType::Array(Box::new(convert(elem, names))),
+        Type::Tuple(elems) => {
+            TsType::Tuple(elems.iter().map(|elem| convert(elem, names)).collect())
+        }
+        Type::Promise(value) => TsType::Promise(Box::new(convert(value, names))),
+        Type::Dict(value) => TsType::Record(Box::new(convert(value, names))),
+        Type::Var(point) => {
+            // Unbound variables that aren't generics are left to the caller as 'any'
+            let name = names.get(&point.id()).map_or("any", String::as_str);
+            TsType::SimpleType(name.to_string())
         }
         Type::Record(row) => {
-            let fields = row_to_fields(row);
+            let fields = row_to_fields(row, names);
             TsType::Object(fields)
         }
-        Type::DiscriminatedUnion(map) => {
+        Type::DiscriminatedUnion(tag, map) => {
             let mut variants: Vec<TsType> = Vec::new();
             for (k, row) in map {
-                let mut fields = row_to_fields(row);
-                fields.insert("type".to_string(), TsType::SimpleType(format!("\"{}\"", k)));
+                let mut fields = row_to_fields(row, names);
+                fields.insert(tag.clone(), TsType::SimpleType(format!("\"{}\"", k)));
                 variants.push(TsType::Object(fields));
             }
             TsType::Union(variants)
@@ -96,7 +535,7 @@ pub fn type_to_ts_type(ty: &Type) -> TsType {
             let mut fields = BTreeMap::new();
             for (name, ty) in attributes {
                 let canonical = canonical_type(ty);
-                fields.insert(name.clone(), type_to_ts_type(&canonical));
+                fields.insert(name.clone(), convert(&canonical, names));
             }
             TsType::View(fields)
         }
@@ -104,7 +543,10 @@ pub fn type_to_ts_type(ty: &Type) -> TsType {
 }
 
 /// Extract object fields from a row descriptor
-fn row_to_fields(row: &crate::type_system::uf::Point<RowDescriptor>) -> BTreeMap<String, TsType> {
+fn row_to_fields(
+    row: &crate::type_system::uf::Point<RowDescriptor>,
+    names: &HashMap<usize, String>,
+) -> BTreeMap<String, TsType> {
     let mut fields = BTreeMap::new();
     let descriptor = uf::get(row);
 
@@ -113,16 +555,45 @@ fn row_to_fields(row: &crate::type_system::uf::Point<RowDescriptor>) -> BTreeMap
             // Add fields from this row extension
             for (name, ty) in row_fields {
                 let canonical = canonical_type(&ty);
-                fields.insert(name.clone(), type_to_ts_type(&canonical));
+                fields.insert(name.clone(), convert(&canonical, names));
             }
             // Recursively collect fields from the rest of the row
-            let rest_fields = row_to_fields(&rest);
+            let rest_fields = row_to_fields(&rest, names);
             fields.extend(rest_fields);
         }
-        RowDescriptor::RowFlex(_) => {
-            // Open row, no additional fields
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => {
+            // No additional fields
         }
     }
 
     fields
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simple(name: &str) -> TsType {
+        TsType::SimpleType(name.to_string())
+    }
+
+    #[test]
+    fn readonly_nested_array() {
+        let ty = TsType::Array(Box::new(TsType::Array(Box::new(simple("string")))));
+        assert_eq!(ty.to_string(), "string[][]");
+        assert_eq!(format!("{:#}", ty), "readonly (readonly string[])[]");
+    }
+
+    #[test]
+    fn readonly_array_of_tuples() {
+        let ty = TsType::Array(Box::new(TsType::Tuple(vec![
+            simple("number"),
+            simple("string"),
+        ])));
+        assert_eq!(ty.to_string(), "[number, string][]");
+        assert_eq!(
+            format!("{:#}", ty),
+            "readonly (readonly [number, string])[]"
+        );
+    }
+}
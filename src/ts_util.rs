@@ -14,3 +14,12 @@ pub fn render_key(k: &str) -> String {
         format!("\"{}\"", k)
     }
 }
+
+/// Accesses property `k` of `object`, using brackets when `k` is not an identifier
+pub fn render_property(object: &str, k: &str) -> String {
+    if is_ts_identifier(k) {
+        format!("{}.{}", object, k)
+    } else {
+        format!("{}[\"{}\"]", object, k)
+    }
+}
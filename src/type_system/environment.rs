@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, HashMap};
 
 use crate::builtins::{BuiltinType, BUILTINS};
+use crate::lang::Span;
 use crate::type_system::types::{Descriptor, FlexMark, Name, RowDescriptor};
 use crate::type_system::uf::{fresh, Point};
 use crate::type_system::Type;
@@ -16,6 +17,8 @@ pub struct InferContext {
 pub struct Env {
     scopes: Vec<TypeMap>,
     globals: TypeMap,
+    /// Where each global was first referenced
+    global_spans: HashMap<Name, Span>,
 }
 
 impl Env {
@@ -35,7 +38,21 @@ impl Env {
         &self.globals
     }
 
-    pub fn get(&mut self, ctx: &mut InferContext, name: &Name) -> Type {
+    /// Whether `name` refers to a scoped variable, such as a loop item, rather than an input
+    pub fn is_local(&self, name: &Name) -> bool {
+        self.scopes.iter().any(|scope| scope.contains_key(name))
+    }
+
+    /// Whether `name` refers to a builtin function, rather than being shadowed by a variable
+    pub fn is_builtin(&self, name: &Name) -> bool {
+        !self.is_local(name) && !self.globals.contains_key(name) && BUILTINS.contains_key(name)
+    }
+
+    pub fn global_span(&self, name: &Name) -> Option<&Span> {
+        self.global_spans.get(name)
+    }
+
+    pub fn get(&mut self, ctx: &mut InferContext, name: &Name, span: &Span) -> Type {
         for scope in self.scopes.iter().rev() {
             if let Some(ty) = scope.get(name) {
                 return ty.clone();
@@ -48,6 +65,7 @@ impl Env {
                 let point = ctx.fresh_named(name);
                 let ty = Type::Var(point.clone());
                 self.globals.insert(name.clone(), ty.clone());
+                self.global_spans.insert(name.clone(), *span);
                 ty
             })
     }
@@ -79,6 +97,7 @@ fn instantiate(
 ) -> Type {
     match builtin_type {
         BuiltinType::Prim(name) => Type::Prim(name.clone()),
+        BuiltinType::Dict(value) => Type::Dict(Box::new(instantiate(value, vars, ctx))),
         BuiltinType::Var(id) => {
             if let Some(point) = vars.get(id) {
                 Type::Var(point.clone())
@@ -107,7 +126,7 @@ fn instantiate_type(
     seen_rows: &mut HashMap<usize, Point<RowDescriptor>>,
 ) -> Type {
     match ty {
-        Type::Prim(_) => ty.clone(),
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => ty.clone(),
         Type::Fun(args, ret) => {
             let new_args = args
                 .iter()
@@ -120,9 +139,24 @@ fn instantiate_type(
             let new_elem = Box::new(instantiate_type(elem, ctx, seen_vars, seen_rows));
             Type::Array(new_elem)
         }
+        Type::Tuple(elems) => {
+            let new_elems = elems
+                .iter()
+                .map(|elem| instantiate_type(elem, ctx, seen_vars, seen_rows))
+                .collect();
+            Type::Tuple(new_elems)
+        }
+        Type::Promise(value) => {
+            let new_value = Box::new(instantiate_type(value, ctx, seen_vars, seen_rows));
+            Type::Promise(new_value)
+        }
+        Type::Dict(value) => {
+            let new_value = Box::new(instantiate_type(value, ctx, seen_vars, seen_rows));
+            Type::Dict(new_value)
+        }
         Type::Var(p) => instantiate_var(p, ctx, seen_vars, seen_rows),
         Type::Record(p) => Type::Record(instantiate_row(p, ctx, seen_vars, seen_rows, true)),
-        Type::DiscriminatedUnion(branches) => {
+        Type::DiscriminatedUnion(tag, branches) => {
             let new_branches = branches
                 .iter()
                 .map(|(k, p)| {
@@ -130,7 +164,7 @@ fn instantiate_type(
                     (k.clone(), new_p)
                 })
                 .collect();
-            Type::DiscriminatedUnion(new_branches)
+            Type::DiscriminatedUnion(tag.clone(), new_branches)
         }
         Type::View(attributes) => {
             let new_attrs = attributes
@@ -180,7 +214,8 @@ fn instantiate_row(
     }
 
     let fresh = match get(p) {
-        RowDescriptor::RowFlex(_) => ctx.fresh_row_point(),
+        // Closed rows come from declarations and reopen at each use, so callers may pass wider records
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => ctx.fresh_row_point(),
         RowDescriptor::RowExtend(fields, _rest) => {
             if collect_fields {
                 let (all_fields, tail) = collect_row_fields(p, ctx, seen_vars, seen_rows);
@@ -209,7 +244,9 @@ fn collect_row_fields(
     use crate::type_system::uf::get;
 
     match get(p) {
-        RowDescriptor::RowFlex(_) => (BTreeMap::new(), ctx.fresh_row_point()),
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => {
+            (BTreeMap::new(), ctx.fresh_row_point())
+        }
         RowDescriptor::RowExtend(fields, rest) => {
             let mut all_fields: BTreeMap<Name, Type> = fields
                 .iter()
@@ -250,6 +287,11 @@ impl InferContext {
         fresh(id, RowDescriptor::RowFlex(mark))
     }
 
+    pub fn fresh_empty_row(&mut self) -> Point<RowDescriptor> {
+        let id = self.allocate_id();
+        fresh(id, RowDescriptor::RowEmpty)
+    }
+
     pub fn fresh_row_extend(
         &mut self,
         fields: std::collections::BTreeMap<Name, crate::type_system::types::Type>,
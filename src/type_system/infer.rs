@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 
 use crate::lang::{Expr, Span, StringTemplateSegment};
 use crate::type_system::environment::{Env, InferContext};
-use crate::type_system::types::{Constraint, Expected, RowDescriptor, Type};
+use crate::type_system::types::{Constraint, Expected, Literal, Origin, RowDescriptor, Type};
 
 pub fn infer(
     ctx: &mut InferContext,
@@ -14,12 +14,18 @@ pub fn infer(
 ) -> Type {
     match expr {
         Expr::Variable(name, span) => {
-            let ty = env.get(ctx, name);
+            let ty = env.get(ctx, name, span);
             expect_equal(span, &ty, &expected, constraints);
             ty
         }
-        Expr::Number(_, span) => {
-            let ty = Type::Prim("number".to_string());
+        Expr::Number(value, span) => {
+            let ty = constant(
+                ctx,
+                span,
+                Literal::Number(value.clone()),
+                &expected,
+                constraints,
+            );
             expect_equal(span, &ty, &expected, constraints);
             ty
         }
@@ -32,12 +38,47 @@ pub fn infer(
                         env,
                         constraints,
                         interpolated_expr,
-                        Expected::Expect(Type::Prim("string".to_string())),
+                        Expected::Expect(Type::Prim("string".to_string()), Origin::Text),
                     );
                 }
             }
-            let ty = Type::Prim("string".to_string());
+            let ty = match segments.as_slice() {
+                [] => constant(
+                    ctx,
+                    span,
+                    Literal::String(String::new()),
+                    &expected,
+                    constraints,
+                ),
+                [StringTemplateSegment::Literal(text)] => constant(
+                    ctx,
+                    span,
+                    Literal::String(text.clone()),
+                    &expected,
+                    constraints,
+                ),
+                _ => Type::Prim("string".to_string()),
+            };
+            expect_equal(span, &ty, &expected, constraints);
+            ty
+        }
+        Expr::Array(elems, span) => {
+            // The tuple is constrained first, so that its elements know what is expected of them
+            let elem_types = elems
+                .iter()
+                .map(|_| Type::Var(ctx.fresh_point()))
+                .collect_vec();
+            let ty = Type::Tuple(elem_types.clone());
             expect_equal(span, &ty, &expected, constraints);
+            for (elem, elem_type) in elems.iter().zip(elem_types) {
+                infer(
+                    ctx,
+                    env,
+                    constraints,
+                    elem,
+                    Expected::Expect(elem_type, Origin::Expression),
+                );
+            }
             ty
         }
         Expr::FunctionCall { callee, args, span } => {
@@ -51,16 +92,26 @@ pub fn infer(
                 Box::new(fresh_ret_type.clone()),
             );
 
+            let origin = match callee.as_ref() {
+                Expr::Variable(name, _) if env.is_builtin(name) => Origin::Builtin(name.clone()),
+                _ => Origin::Expression,
+            };
             infer(
                 ctx,
                 env,
                 constraints,
                 callee,
-                Expected::Expect(expected_fn_type),
+                Expected::Expect(expected_fn_type, origin),
             );
 
             for (arg, ty) in fresh_arg_types {
-                infer(ctx, env, constraints, arg, Expected::Expect(ty));
+                infer(
+                    ctx,
+                    env,
+                    constraints,
+                    arg,
+                    Expected::Expect(ty, Origin::Expression),
+                );
             }
 
             expect_equal(span, &fresh_ret_type, &expected, constraints);
@@ -81,7 +132,13 @@ pub fn infer(
             };
 
             let wanted_row = Type::Record(row_point);
-            infer(ctx, env, constraints, rec, Expected::Expect(wanted_row));
+            infer(
+                ctx,
+                env,
+                constraints,
+                rec,
+                Expected::Expect(wanted_row, Origin::Expression),
+            );
 
             expect_equal(span, &field_type, &expected, constraints);
 
@@ -119,6 +176,31 @@ pub fn infer(
     }
 }
 
+/// A constant gets its literal type only where a literal type is expected, and widens
+/// to its primitive everywhere else. What is expected is often only known once earlier
+/// constraints are solved, such as the parameters of a called function.
+fn constant(
+    ctx: &mut InferContext,
+    span: &Span,
+    literal: Literal,
+    expected: &Expected,
+    constraints: &mut Vec<Constraint>,
+) -> Type {
+    match expected {
+        Expected::Expect(target, _) => {
+            let ty = Type::Var(ctx.fresh_point());
+            constraints.push(Constraint::Constant(
+                *span,
+                literal,
+                ty.clone(),
+                target.clone(),
+            ));
+            ty
+        }
+        Expected::NoExpect => Type::Prim(literal.primitive().to_string()),
+    }
+}
+
 fn expect_equal(
     span: &Span,
     actual: &Type,
@@ -126,8 +208,13 @@ fn expect_equal(
     constraints: &mut Vec<Constraint>,
 ) {
     match expected {
-        Expected::Expect(target) => {
-            constraints.push(Constraint::Equal(*span, actual.clone(), target.clone()));
+        Expected::Expect(target, origin) => {
+            constraints.push(Constraint::Equal(
+                *span,
+                actual.clone(),
+                target.clone(),
+                origin.clone(),
+            ));
         }
         Expected::NoExpect => {}
     }
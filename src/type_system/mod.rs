@@ -11,14 +11,15 @@ mod tests {
     use super::*;
     use crate::lang::expr_parser;
     use crate::lang::Expr;
-    use crate::ts_type::env_to_ts_type;
+    use crate::ts_type::{env_to_generic_ts_type, type_to_ts_type, TsType};
+    use chumsky::span::Span as _;
     use chumsky::Parser;
     use environment::{Env, InferContext};
     use infer::infer;
     pub use solver::TypeError;
-    use solver::{canonical_type, solve};
+    use solver::{canonical_type, solve, ExplainedError};
     use std::collections::{BTreeMap, HashMap};
-    use types::{Descriptor, Expected, Name, RowDescriptor, Type};
+    use types::{Descriptor, Expected, Name, Origin, RowDescriptor, Type};
 
     fn read_env_types(env: &Env) -> HashMap<Name, Type> {
         let mut out = HashMap::new();
@@ -35,7 +36,7 @@ mod tests {
         pub env: Env,
     }
 
-    fn infer_types(expr: &Expr) -> Result<InferResult, TypeError> {
+    fn infer_types(expr: &Expr) -> Result<InferResult, Vec<ExplainedError>> {
         let mut ctx = InferContext::new();
         let mut env = Env::default();
         let mut constraints = Vec::new();
@@ -60,7 +61,23 @@ mod tests {
         let parser = expr_parser(0);
         let expr = parser.parse(input).into_result().unwrap();
         let result = infer_types(&expr).unwrap();
-        let ts_type = env_to_ts_type(&result.env);
+        let ts_type = TsType::Object(
+            result
+                .env_types
+                .iter()
+                .map(|(name, ty)| (name.clone(), type_to_ts_type(ty)))
+                .collect(),
+        );
+        assert_eq!(ts_type.to_string(), expected, "for input: {}", input);
+    }
+
+    /// Like `check`, but naming the variables that occur more than once as generics
+    fn check_generic(input: &str, expected_generics: &[&str], expected: &str) {
+        let parser = expr_parser(0);
+        let expr = parser.parse(input).into_result().unwrap();
+        let result = infer_types(&expr).unwrap();
+        let (generics, ts_type) = env_to_generic_ts_type(&result.env, &[]);
+        assert_eq!(generics, expected_generics, "for input: {}", input);
         assert_eq!(ts_type.to_string(), expected, "for input: {}", input);
     }
 
@@ -126,6 +143,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn chained_pipe_generics() {
+        check_generic(
+            "a | f1 | f2",
+            &["T", "U"],
+            "{ a: T, f1: (v0: T) => U, f2: (v0: U) => any }",
+        );
+    }
+
     #[test]
     fn string_with_field() {
         check(r#""User: {user.name}""#, "{ user: { name: string } }");
@@ -144,6 +170,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lookup_takes_a_dictionary() {
+        check(
+            "lookup(cart.items, id, 0)",
+            "{ cart: { items: Record<string, number> }, id: string }",
+        );
+    }
+
     #[test]
     fn instantiate_unbound_var() {
         let mut ctx = InferContext::new();
@@ -276,14 +310,15 @@ mod tests {
         let mut branches = BTreeMap::new();
         branches.insert("a".to_string(), ctx.fresh_row_point());
         branches.insert("b".to_string(), ctx.fresh_row_point());
-        let du = Type::DiscriminatedUnion(branches);
+        let du = Type::DiscriminatedUnion("kind".to_string(), branches);
         let instantiated = ctx.instantiate(&du);
 
         match instantiated {
-            Type::DiscriminatedUnion(new_branches) => {
+            Type::DiscriminatedUnion(tag, new_branches) => {
+                assert_eq!(tag, "kind");
                 assert_eq!(new_branches.len(), 2);
                 let orig_branches = match &du {
-                    Type::DiscriminatedUnion(b) => b,
+                    Type::DiscriminatedUnion(_, b) => b,
                     _ => unreachable!(),
                 };
                 for (k, p) in new_branches {
@@ -403,4 +438,239 @@ mod tests {
             _ => panic!("Expected Record"),
         }
     }
+
+    fn closed_record(ctx: &mut InferContext, field: &str) -> Type {
+        let mut fields = BTreeMap::new();
+        fields.insert(field.to_string(), Type::Prim("string".to_string()));
+        let closed = ctx.fresh_empty_row();
+        Type::Record(ctx.fresh_row_extend(fields, closed))
+    }
+
+    fn open_record(ctx: &mut InferContext, field: &str) -> Type {
+        let mut fields = BTreeMap::new();
+        fields.insert(field.to_string(), Type::Var(ctx.fresh_point()));
+        let rest = ctx.fresh_row_point();
+        Type::Record(ctx.fresh_row_extend(fields, rest))
+    }
+
+    #[test]
+    fn closed_record_accepts_listed_fields() {
+        let mut ctx = InferContext::new();
+        let declared = closed_record(&mut ctx, "name");
+        let used = open_record(&mut ctx, "name");
+        let constraints = vec![types::Constraint::Equal(
+            crate::lang::Span::new(0, 0..0),
+            declared,
+            used,
+            Origin::Expression,
+        )];
+        assert!(solve(&mut ctx, &constraints).is_ok());
+    }
+
+    #[test]
+    fn closed_record_rejects_extra_fields() {
+        let mut ctx = InferContext::new();
+        let declared = closed_record(&mut ctx, "name");
+        let used = open_record(&mut ctx, "nmae");
+        let constraints = vec![types::Constraint::Equal(
+            crate::lang::Span::new(0, 0..0),
+            declared,
+            used,
+            Origin::Expression,
+        )];
+        match solve(&mut ctx, &constraints) {
+            Err(errors) => match errors.as_slice() {
+                [ExplainedError {
+                    error: TypeError::RowMismatch { message, .. },
+                    ..
+                }] => assert!(message.contains("'nmae'")),
+                other => panic!("Expected one RowMismatch, got {:?}", other),
+            },
+            other => panic!("Expected RowMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn instantiate_reopens_closed_record() {
+        use crate::type_system::uf::get;
+
+        let mut ctx = InferContext::new();
+        let declared = closed_record(&mut ctx, "name");
+        match ctx.instantiate(&declared) {
+            Type::Record(point) => match get(&point) {
+                RowDescriptor::RowExtend(_, rest) => {
+                    assert!(matches!(get(&rest), RowDescriptor::RowFlex(_)))
+                }
+                other => panic!("Expected RowExtend, got {:?}", other),
+            },
+            other => panic!("Expected Record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn imported_type_is_accepted_as_dictionary() {
+        let mut ctx = InferContext::new();
+        let constraints = vec![types::Constraint::Equal(
+            crate::lang::Span::new(0, 0..0),
+            Type::Named("Prices".to_string()),
+            Type::Dict(Box::new(Type::Var(ctx.fresh_point()))),
+            Origin::Builtin("lookup".to_string()),
+        )];
+        assert!(solve(&mut ctx, &constraints).is_ok());
+    }
+
+    #[test]
+    fn promise_value_type_unifies() {
+        let mut ctx = InferContext::new();
+        let value = Type::Var(ctx.fresh_point());
+        let constraints = vec![types::Constraint::Equal(
+            crate::lang::Span::new(0, 0..0),
+            Type::Promise(Box::new(value.clone())),
+            Type::Promise(Box::new(Type::Prim("string".to_string()))),
+            Origin::Expression,
+        )];
+        assert!(solve(&mut ctx, &constraints).is_ok());
+        assert_eq!(canonical_type(&value), Type::Prim("string".to_string()));
+    }
+
+    fn literals(values: &[&str]) -> Type {
+        Type::Literal(
+            values
+                .iter()
+                .map(|value| types::Literal::String(value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn literal_types_are_checked_against_their_members() {
+        let mut ctx = InferContext::new();
+        let span = crate::lang::Span::new(0, 0..0);
+        let variant = Type::Var(ctx.fresh_point());
+        let constraints = vec![
+            // A literal is accepted where its primitive is expected
+            types::Constraint::Equal(
+                span,
+                literals(&["primary"]),
+                Type::Prim("string".to_string()),
+                Origin::Expression,
+            ),
+            types::Constraint::Equal(
+                span,
+                literals(&["primary"]),
+                literals(&["primary", "secondary"]),
+                Origin::Expression,
+            ),
+            // An inferred type narrows to the literals it is expected to be
+            types::Constraint::Equal(
+                span,
+                variant.clone(),
+                Type::Prim("string".to_string()),
+                Origin::Expression,
+            ),
+            types::Constraint::Equal(
+                span,
+                variant.clone(),
+                literals(&["primary", "secondary"]),
+                Origin::Expression,
+            ),
+        ];
+        assert!(solve(&mut ctx, &constraints).is_ok());
+        assert_eq!(
+            canonical_type(&variant),
+            literals(&["primary", "secondary"])
+        );
+
+        let constraints = vec![types::Constraint::Equal(
+            span,
+            literals(&["tertiary"]),
+            literals(&["primary", "secondary"]),
+            Origin::Expression,
+        )];
+        assert!(solve(&mut ctx, &constraints).is_err());
+    }
+
+    #[test]
+    fn declared_type_is_not_narrowed_to_literals() {
+        let mut ctx = InferContext::new();
+        let span = crate::lang::Span::new(0, 0..0);
+        let variant = Type::Var(ctx.fresh_point());
+        let constraints = vec![
+            types::Constraint::Equal(
+                span,
+                variant.clone(),
+                Type::Prim("string".to_string()),
+                Origin::Declaration("variant".to_string()),
+            ),
+            types::Constraint::Equal(
+                span,
+                variant.clone(),
+                literals(&["primary", "secondary"]),
+                Origin::Expression,
+            ),
+        ];
+        assert!(solve(&mut ctx, &constraints).is_err());
+        assert_eq!(canonical_type(&variant), Type::Error);
+    }
+
+    #[test]
+    fn array_literal_is_a_tuple() {
+        check(
+            "pick([first, 2])",
+            "{ first: any, pick: (v0: [any, number]) => any }",
+        );
+    }
+
+    #[test]
+    fn solve_reports_every_failing_constraint_once() {
+        let mut ctx = InferContext::new();
+        let span = crate::lang::Span::new(0, 0..0);
+        let prim = |name: &str| Type::Prim(name.to_string());
+        let count = Type::Var(ctx.fresh_point());
+        let label = Type::Var(ctx.fresh_point());
+        let constraints = vec![
+            types::Constraint::Equal(span, count.clone(), prim("number"), Origin::Expression),
+            types::Constraint::Equal(span, count.clone(), prim("string"), Origin::Expression),
+            // Already reported for count, so this is not reported again
+            types::Constraint::Equal(span, count, prim("boolean"), Origin::Expression),
+            types::Constraint::Equal(span, label.clone(), prim("string"), Origin::Expression),
+            types::Constraint::Equal(span, label, prim("number"), Origin::Expression),
+        ];
+        match solve(&mut ctx, &constraints) {
+            Err(errors) => assert_eq!(errors.len(), 2, "{:?}", errors),
+            Ok(()) => panic!("Expected errors"),
+        }
+    }
+
+    #[test]
+    fn solve_explains_where_a_conflicting_type_came_from() {
+        let mut ctx = InferContext::new();
+        let condition_span = crate::lang::Span::new(0, 10..19);
+        let use_span = crate::lang::Span::new(0, 40..45);
+        let flag = Type::Var(ctx.fresh_point());
+        let constraints = vec![
+            types::Constraint::Equal(
+                condition_span,
+                flag.clone(),
+                Type::Prim("boolean".to_string()),
+                Origin::attribute("if", "condition"),
+            ),
+            types::Constraint::Equal(
+                use_span,
+                flag,
+                Type::Prim("number".to_string()),
+                Origin::Expression,
+            ),
+        ];
+        match solve(&mut ctx, &constraints) {
+            Err(errors) => assert_eq!(
+                errors[0].reasons,
+                vec![(
+                    condition_span,
+                    "Typed as boolean by the 'condition' attribute of <if>".to_string()
+                )]
+            ),
+            Ok(()) => panic!("Expected an error"),
+        }
+    }
 }
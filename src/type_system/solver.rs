@@ -1,10 +1,13 @@
-use std::collections::BTreeMap;
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use crate::error::Error as VegenError;
 use crate::lang::Span;
 use crate::ts_type::type_to_ts_type;
 use crate::type_system::environment::InferContext;
-use crate::type_system::types::{Constraint, Descriptor, FlexMark, Name, RowDescriptor, Type};
+use crate::type_system::types::{
+    Constraint, Descriptor, FlexMark, Literal, Name, Origin, RowDescriptor, Type,
+};
 use crate::type_system::uf::{get, set, union, Point};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,8 +76,8 @@ impl TypeError {
                 expected,
                 actual,
             } => {
-                let expected = type_to_ts_type(expected).to_string();
-                let actual = type_to_ts_type(actual).to_string();
+                let expected = type_to_ts_type(&canonical_type(expected)).to_string();
+                let actual = type_to_ts_type(&canonical_type(actual)).to_string();
                 let labels = vec![(*span, format!("This has type {}", actual))];
                 let message = format!(
                     "Type structure mismatch: expected {}, got {}",
@@ -117,18 +120,141 @@ impl TypeError {
     }
 }
 
-pub fn solve(ctx: &mut InferContext, constraints: &Vec<Constraint>) -> Result<(), TypeError> {
+/// A type error, with the earlier uses that gave the conflicting types their types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainedError {
+    pub error: TypeError,
+    pub reasons: Vec<(Span, String)>,
+}
+
+impl ExplainedError {
+    pub fn to_error(&self) -> VegenError {
+        let mut error = self.error.to_error();
+        error.labels.extend(self.reasons.iter().cloned());
+        error
+    }
+}
+
+struct Solver<'a> {
+    ctx: &'a mut InferContext,
+    /// Origin of the constraint being solved
+    origin: Origin,
+    /// Span and origin of the constraint that bound each type variable
+    provenance: HashMap<usize, (Span, Origin)>,
+    /// Provenance of the types whose insides are being unified, which the variables bound
+    /// inside them share
+    enclosing: Option<(Span, Origin)>,
+    /// Explanations gathered while the current constraint fails
+    reasons: Vec<(Span, String)>,
+}
+
+impl Solver<'_> {
+    /// The constraint that gave a bound variable its type, following variables bound to variables
+    fn provenance_of(&self, ty: &Type) -> Option<&(Span, Origin)> {
+        let Type::Var(point) = ty else {
+            return None;
+        };
+        match get(point) {
+            Descriptor::Bound(bound) => self
+                .provenance
+                .get(&point.id())
+                .or_else(|| self.provenance_of(&bound)),
+            Descriptor::Unbound(_) => None,
+        }
+    }
+
+    /// Record that `point` was bound by the current constraint. Constraints from the structure
+    /// of an expression only pass a type along, so the binding keeps the type's provenance.
+    fn record_binding(&mut self, span: &Span, point: &Point<Descriptor>, from: &[&Type]) {
+        let provenance = match &self.origin {
+            Origin::Expression => from
+                .iter()
+                .find_map(|ty| self.provenance_of(ty))
+                .or(self.enclosing.as_ref())
+                .cloned()
+                .unwrap_or((*span, Origin::Expression)),
+            origin => (*span, origin.clone()),
+        };
+        self.provenance.insert(point.id(), provenance);
+    }
+
+    /// Whether a type was given to a variable by inference, rather than by a declaration,
+    /// so that it may be narrowed
+    fn is_inferred(&self, ty: &Type) -> bool {
+        bound_point(ty).is_some()
+            && !matches!(self.provenance_of(ty), Some((_, Origin::Declaration(_))))
+    }
+
+    fn explain(&mut self, span: &Span, ty: &Type) {
+        let Some((reason_span, origin)) = self.provenance_of(ty).cloned() else {
+            return;
+        };
+        if reason_span == *span || self.reasons.iter().any(|(s, _)| *s == reason_span) {
+            return;
+        }
+        let ty = type_to_ts_type(&canonical_type(ty));
+        let message = match origin {
+            Origin::Expression => format!("Typed as {} here", ty),
+            origin => format!("Typed as {} by {}", ty, origin),
+        };
+        self.reasons.push((reason_span, message));
+    }
+}
+
+/// Solve every constraint, collecting the errors rather than stopping at the first. The
+/// variables of a failing constraint are poisoned so that their other uses aren't reported.
+pub fn solve(
+    ctx: &mut InferContext,
+    constraints: &Vec<Constraint>,
+) -> Result<(), Vec<ExplainedError>> {
+    let mut solver = Solver {
+        ctx,
+        origin: Origin::Expression,
+        provenance: HashMap::new(),
+        enclosing: None,
+        reasons: Vec::new(),
+    };
+    let mut errors = Vec::new();
     for constraint in constraints {
         match constraint {
-            Constraint::Equal(span, t1, t2) => unify(ctx, span, t1, t2)?,
+            Constraint::Equal(span, t1, t2, origin) => {
+                solver.origin = origin.clone();
+                if let Err(error) = unify(&mut solver, span, t1, t2) {
+                    poison(t1);
+                    poison(t2);
+                    errors.push(ExplainedError {
+                        error,
+                        reasons: std::mem::take(&mut solver.reasons),
+                    });
+                }
+            }
+            Constraint::Constant(span, literal, ty, expected) => {
+                solver.origin = Origin::Expression;
+                let constant = match resolve(expected) {
+                    Type::Literal(_) => Type::Literal(BTreeSet::from([literal.clone()])),
+                    _ => Type::Prim(literal.primitive().to_string()),
+                };
+                // The constant's variable is fresh, so this can't fail
+                let _ = unify(&mut solver, span, ty, &constant);
+            }
         }
     }
-    Ok(())
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn poison(ty: &Type) {
+    if let Type::Var(point) = ty {
+        set(point, Descriptor::Bound(Box::new(Type::Error)));
+    }
 }
 
 pub fn canonical_type(ty: &Type) -> Type {
     match ty {
-        Type::Prim(_) => ty.clone(),
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => ty.clone(),
         Type::Fun(args, res) => {
             let args = args.iter().map(canonical_type).collect();
             let res = Box::new(canonical_type(res));
@@ -138,6 +264,15 @@ pub fn canonical_type(ty: &Type) -> Type {
             let elem = Box::new(canonical_type(elem));
             Type::Array(elem)
         }
+        Type::Tuple(elems) => Type::Tuple(elems.iter().map(canonical_type).collect()),
+        Type::Promise(value) => {
+            let value = Box::new(canonical_type(value));
+            Type::Promise(value)
+        }
+        Type::Dict(value) => {
+            let value = Box::new(canonical_type(value));
+            Type::Dict(value)
+        }
         Type::Var(point) => match get(point) {
             Descriptor::Bound(bound) => canonical_type(&bound),
             Descriptor::Unbound(_) => Type::Var(point.clone()),
@@ -146,13 +281,13 @@ pub fn canonical_type(ty: &Type) -> Type {
             let row = canonical_row_point(row_point);
             Type::Record(row)
         }
-        Type::DiscriminatedUnion(map) => {
+        Type::DiscriminatedUnion(tag, map) => {
             let mut new_map = BTreeMap::new();
             for (k, rp) in map {
                 let row = canonical_row_point(rp);
                 new_map.insert(k.clone(), row);
             }
-            Type::DiscriminatedUnion(new_map)
+            Type::DiscriminatedUnion(tag.clone(), new_map)
         }
         Type::View(attributes) => {
             let new_attrs = attributes
@@ -164,10 +299,18 @@ pub fn canonical_type(ty: &Type) -> Type {
     }
 }
 
+/// The fields of a record type, or `None` if the type is not a record.
+pub fn record_fields(ty: &Type) -> Option<BTreeMap<Name, Type>> {
+    match canonical_type(ty) {
+        Type::Record(point) => Some(gather_fields(BTreeMap::new(), &point).0),
+        _ => None,
+    }
+}
+
 fn canonical_row_point(row_point: &Point<RowDescriptor>) -> Point<RowDescriptor> {
     let desc = get_row(row_point);
     match desc {
-        RowDescriptor::RowFlex(_) => row_point.clone(),
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => row_point.clone(),
         RowDescriptor::RowExtend(fields, tail) => {
             let mut canonical_fields = BTreeMap::new();
             for (name, ty) in fields {
@@ -187,12 +330,51 @@ fn get_row(point: &Point<RowDescriptor>) -> RowDescriptor {
     get(point)
 }
 
-fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<(), TypeError> {
-    let t1 = canonical_type(t1);
-    let t2 = canonical_type(t2);
+fn unify(solver: &mut Solver, span: &Span, t1: &Type, t2: &Type) -> Result<(), TypeError> {
+    let outer = solver.enclosing.clone();
+    if let Some(provenance) = solver
+        .provenance_of(t1)
+        .or_else(|| solver.provenance_of(t2))
+    {
+        solver.enclosing = Some(provenance.clone());
+    }
+    let result = unify_resolved(solver, span, t1, t2);
+    solver.enclosing = outer;
+    if result.is_err() {
+        solver.explain(span, t1);
+        solver.explain(span, t2);
+    }
+    result
+}
+
+/// Follow bound variables at the top of a type, leaving nested ones in place so that
+/// conflicts within them can be explained too.
+fn resolve(ty: &Type) -> Type {
+    match ty {
+        Type::Var(point) => match get(point) {
+            Descriptor::Bound(bound) => resolve(&bound),
+            Descriptor::Unbound(_) => ty.clone(),
+        },
+        _ => ty.clone(),
+    }
+}
+
+fn unify_resolved(
+    solver: &mut Solver,
+    span: &Span,
+    original1: &Type,
+    original2: &Type,
+) -> Result<(), TypeError> {
+    let t1 = resolve(original1);
+    let t2 = resolve(original2);
     match (t1, t2) {
-        (Type::Var(p1), Type::Var(p2)) => unify_points(ctx, span, &p1, &p2),
-        (Type::Var(point), ty) | (ty, Type::Var(point)) => bind_variable(span, &point, &ty),
+        (Type::Var(p1), Type::Var(p2)) => unify_points(solver, span, &p1, &p2),
+        (Type::Var(point), ty) | (ty, Type::Var(point)) => {
+            bind_variable(span, &point, &ty)?;
+            solver.record_binding(span, &point, &[original1, original2]);
+            Ok(())
+        }
+        (Type::Error, _) | (_, Type::Error) => Ok(()),
         (Type::Fun(args1, res1), Type::Fun(args2, res2)) => {
             if args1.len() != args2.len() {
                 return Err(TypeError::ArityMismatch {
@@ -201,12 +383,43 @@ fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<()
                     actual: args1.len(),
                 });
             }
+            // Arguments flow the other way: a function used where another is expected receives
+            // the expected function's arguments
             for (a1, a2) in args1.iter().zip(args2.iter()) {
-                unify(ctx, span, a1, a2)?;
+                unify(solver, span, a2, a1)?;
             }
-            unify(ctx, span, &res1, &res2)
+            unify(solver, span, &res1, &res2)
+        }
+        (Type::Array(e1), Type::Array(e2)) => unify(solver, span, &e1, &e2),
+        (Type::Tuple(elems1), Type::Tuple(elems2)) if elems1.len() == elems2.len() => {
+            for (e1, e2) in elems1.iter().zip(elems2.iter()) {
+                unify(solver, span, e1, e2)?;
+            }
+            Ok(())
+        }
+        // A tuple can be used as an array of its elements
+        (Type::Tuple(elems), Type::Array(elem)) => {
+            for e in &elems {
+                unify(solver, span, e, &elem)?;
+            }
+            Ok(())
+        }
+        // An inferred array narrows to a tuple, like a primitive to a literal below
+        (Type::Array(elem), Type::Tuple(elems)) if solver.is_inferred(original1) => {
+            for e in &elems {
+                unify(solver, span, &elem, e)?;
+            }
+            narrow(solver, span, original1, Type::Tuple(elems));
+            Ok(())
+        }
+        (Type::Promise(v1), Type::Promise(v2)) => unify(solver, span, &v1, &v2),
+        (Type::Dict(v1), Type::Dict(v2)) => unify(solver, span, &v1, &v2),
+        (
+            actual @ (Type::Literal(_) | Type::Prim(_)),
+            expected @ (Type::Literal(_) | Type::Prim(_)),
+        ) if matches!(actual, Type::Literal(_)) || matches!(expected, Type::Literal(_)) => {
+            unify_literals(solver, span, original1, &actual, &expected)
         }
-        (Type::Array(e1), Type::Array(e2)) => unify(ctx, span, &e1, &e2),
         (Type::Prim(p1), Type::Prim(p2)) => {
             if p1 == p2 {
                 Ok(())
@@ -218,8 +431,21 @@ fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<()
                 })
             }
         }
-        (Type::Record(r1), Type::Record(r2)) => unify_rows(ctx, span, &r1, &r2),
-        (Type::DiscriminatedUnion(m1), Type::DiscriminatedUnion(m2)) => {
+        (Type::Record(r1), Type::Record(r2)) => unify_rows(solver, span, &r1, &r2),
+        (Type::Named(n1), Type::Named(n2)) if n1 == n2 => Ok(()),
+        // Field access and lookups on an imported type are checked by TypeScript, not here
+        (Type::Named(_), Type::Record(_) | Type::Dict(_) | Type::DiscriminatedUnion(..))
+        | (Type::Record(_) | Type::Dict(_) | Type::DiscriminatedUnion(..), Type::Named(_)) => {
+            Ok(())
+        }
+        (Type::DiscriminatedUnion(tag1, m1), Type::DiscriminatedUnion(tag2, m2)) => {
+            if tag1 != tag2 {
+                return Err(TypeError::StructMismatch {
+                    span: *span,
+                    expected: Type::DiscriminatedUnion(tag2, m2),
+                    actual: Type::DiscriminatedUnion(tag1, m1),
+                });
+            }
             let keys1: Vec<String> = m1.keys().cloned().collect();
             let keys2: Vec<String> = m2.keys().cloned().collect();
             if keys1 != keys2 {
@@ -231,7 +457,7 @@ fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<()
             }
             for (k, rp1) in m1.iter() {
                 let rp2 = m2.get(k).unwrap();
-                unify_rows(ctx, span, rp1, rp2)?;
+                unify_rows(solver, span, rp1, rp2)?;
             }
             Ok(())
         }
@@ -247,7 +473,7 @@ fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<()
             }
             for (k, v1) in attrs1.iter() {
                 let v2 = attrs2.get(k).unwrap();
-                unify(ctx, span, v1, v2)?;
+                unify(solver, span, v1, v2)?;
             }
             Ok(())
         }
@@ -259,8 +485,81 @@ fn unify(ctx: &mut InferContext, span: &Span, t1: &Type, t2: &Type) -> Result<()
     }
 }
 
+/// A literal type can be used as its primitive, and as a wider union of literals. The other
+/// way around, a type inferred as a primitive or a wider union narrows to the expected
+/// literals, but a type given by a declaration or an expression is a mismatch.
+fn unify_literals(
+    solver: &mut Solver,
+    span: &Span,
+    original_actual: &Type,
+    actual: &Type,
+    expected: &Type,
+) -> Result<(), TypeError> {
+    let mismatch = || TypeError::StructMismatch {
+        span: *span,
+        expected: expected.clone(),
+        actual: actual.clone(),
+    };
+    let (Some((kind1, values1)), Some((kind2, values2))) =
+        (literal_values(actual), literal_values(expected))
+    else {
+        return Err(mismatch());
+    };
+    if kind1 != kind2 {
+        return Err(mismatch());
+    }
+    let narrowed: BTreeSet<Literal> = match (values1, values2) {
+        (_, None) => return Ok(()),
+        (Some(values1), Some(values2)) if values1.is_subset(values2) => return Ok(()),
+        (Some(values1), Some(values2)) => values1.intersection(values2).cloned().collect(),
+        (None, Some(values2)) => values2.clone(),
+    };
+    if narrowed.is_empty() || !solver.is_inferred(original_actual) {
+        return Err(mismatch());
+    }
+    narrow(solver, span, original_actual, Type::Literal(narrowed));
+    Ok(())
+}
+
+/// The primitive a literal or primitive type belongs to, with its literals if it has them
+fn literal_values(ty: &Type) -> Option<(&str, Option<&BTreeSet<Literal>>)> {
+    match ty {
+        Type::Prim(name) if ["string", "number", "boolean"].contains(&name.as_str()) => {
+            Some((name.as_str(), None))
+        }
+        Type::Literal(literals) => {
+            let kind = literals.iter().next()?.primitive();
+            Some((kind, Some(literals)))
+        }
+        _ => None,
+    }
+}
+
+/// The variable holding a type that inference gave to a variable, following variables
+/// bound to variables
+fn bound_point(ty: &Type) -> Option<Point<Descriptor>> {
+    let Type::Var(point) = ty else {
+        return None;
+    };
+    match get(point) {
+        Descriptor::Bound(bound) => match bound.as_ref() {
+            Type::Var(_) => bound_point(&bound),
+            _ => Some(point.clone()),
+        },
+        Descriptor::Unbound(_) => None,
+    }
+}
+
+/// Replace an inferred type with a narrower one
+fn narrow(solver: &mut Solver, span: &Span, ty: &Type, narrowed: Type) {
+    if let Some(point) = bound_point(ty) {
+        set(&point, Descriptor::Bound(Box::new(narrowed)));
+        solver.record_binding(span, &point, &[]);
+    }
+}
+
 fn unify_points(
-    ctx: &mut InferContext,
+    solver: &mut Solver,
     span: &Span,
     p1: &Point<Descriptor>,
     p2: &Point<Descriptor>,
@@ -273,8 +572,16 @@ fn unify_points(
     let desc2 = get(p2);
 
     match (desc1.clone(), desc2.clone()) {
-        (Descriptor::Bound(bound), _) => unify(ctx, span, &bound, &Type::Var(p2.clone())),
-        (_, Descriptor::Bound(bound)) => unify(ctx, span, &Type::Var(p1.clone()), &bound),
+        (Descriptor::Bound(bound), _) => {
+            unify(solver, span, &bound, &Type::Var(p2.clone()))?;
+            solver.record_binding(span, p2, &[&Type::Var(p1.clone())]);
+            Ok(())
+        }
+        (_, Descriptor::Bound(bound)) => {
+            unify(solver, span, &Type::Var(p1.clone()), &bound)?;
+            solver.record_binding(span, p1, &[&Type::Var(p2.clone())]);
+            Ok(())
+        }
         (Descriptor::Unbound(mark1), Descriptor::Unbound(mark2)) => {
             let descriptor = merge_marks(mark1, mark2);
             union(p1, p2, Descriptor::Unbound(descriptor));
@@ -303,7 +610,7 @@ fn bind_variable(span: &Span, point: &Point<Descriptor>, ty: &Type) -> Result<()
 fn occurs(point: &Point<Descriptor>, ty: &Type) -> bool {
     match canonical_type(ty) {
         Type::Var(p) => &p == point,
-        Type::Prim(_) => false,
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => false,
         Type::Fun(args, res) => {
             for arg in args {
                 if occurs(point, &arg) {
@@ -312,9 +619,10 @@ fn occurs(point: &Point<Descriptor>, ty: &Type) -> bool {
             }
             occurs(point, &res)
         }
-        Type::Array(elem) => occurs(point, &elem),
+        Type::Array(elem) | Type::Promise(elem) | Type::Dict(elem) => occurs(point, &elem),
+        Type::Tuple(elems) => elems.iter().any(|elem| occurs(point, elem)),
         Type::Record(row_point) => occurs_in_row(point, &row_point),
-        Type::DiscriminatedUnion(map) => {
+        Type::DiscriminatedUnion(_, map) => {
             for (_, rp) in map {
                 if occurs_in_row(point, &rp) {
                     return true;
@@ -336,7 +644,7 @@ fn occurs(point: &Point<Descriptor>, ty: &Type) -> bool {
 fn occurs_in_row(point: &Point<Descriptor>, row_point: &Point<RowDescriptor>) -> bool {
     let desc = get_row(row_point);
     match desc {
-        RowDescriptor::RowFlex(_) => false,
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => false,
         RowDescriptor::RowExtend(fields, tail) => {
             for (_, ty) in fields {
                 if occurs(point, &ty) {
@@ -363,7 +671,7 @@ fn gather_fields(
                 }
                 current = sub_ext;
             }
-            RowDescriptor::RowFlex(_) => {
+            RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => {
                 break;
             }
         }
@@ -372,7 +680,7 @@ fn gather_fields(
 }
 
 fn unify_rows(
-    ctx: &mut InferContext,
+    solver: &mut Solver,
     span: &Span,
     r1: &Point<RowDescriptor>,
     r2: &Point<RowDescriptor>,
@@ -400,17 +708,27 @@ fn unify_rows(
             union(r1, r2, desc);
             Ok(())
         }
+        (RowDescriptor::RowEmpty, RowDescriptor::RowEmpty) => {
+            union(r1, r2, RowDescriptor::RowEmpty);
+            Ok(())
+        }
+        (RowDescriptor::RowEmpty, RowDescriptor::RowExtend(_, _)) => {
+            Err(unexpected_fields_error(span, r2))
+        }
+        (RowDescriptor::RowExtend(_, _), RowDescriptor::RowEmpty) => {
+            Err(unexpected_fields_error(span, r1))
+        }
         (RowDescriptor::RowExtend(_, _), RowDescriptor::RowExtend(_, _)) => {
             // Gather all fields recursively
             let structure1 = gather_fields(BTreeMap::new(), r1);
             let structure2 = gather_fields(BTreeMap::new(), r2);
-            unify_record_structure(ctx, span, structure1, structure2)
+            unify_record_structure(solver, span, structure1, structure2)
         }
     }
 }
 
 fn unify_record_structure(
-    ctx: &mut InferContext,
+    solver: &mut Solver,
     span: &Span,
     (fields1, ext1): (BTreeMap<Name, Type>, Point<RowDescriptor>),
     (fields2, ext2): (BTreeMap<Name, Type>, Point<RowDescriptor>),
@@ -421,7 +739,7 @@ fn unify_record_structure(
 
     for (name, ty1) in fields1 {
         if let Some(ty2) = unique_fields2.remove(&name) {
-            unify(ctx, span, &ty1, &ty2)?;
+            unify(solver, span, &ty1, &ty2)?;
         } else {
             unique_fields1.insert(name, ty1);
         }
@@ -430,29 +748,39 @@ fn unify_record_structure(
     // Unify based on which sets are empty
     if unique_fields1.is_empty() {
         if unique_fields2.is_empty() {
-            unify_rows(ctx, span, &ext1, &ext2)?;
+            unify_rows(solver, span, &ext1, &ext2)?;
         } else {
-            let sub_record = ctx.fresh_row_extend(unique_fields2, ext2);
-            unify_rows(ctx, span, &ext1, &sub_record)?;
+            let sub_record = solver.ctx.fresh_row_extend(unique_fields2, ext2);
+            unify_rows(solver, span, &ext1, &sub_record)?;
         }
     } else if unique_fields2.is_empty() {
-        let sub_record = ctx.fresh_row_extend(unique_fields1, ext1);
-        unify_rows(ctx, span, &sub_record, &ext2)?;
+        let sub_record = solver.ctx.fresh_row_extend(unique_fields1, ext1);
+        unify_rows(solver, span, &sub_record, &ext2)?;
     } else {
-        let ext = ctx.fresh_row_point();
-        let sub1 = ctx.fresh_row_extend(unique_fields1, ext.clone());
-        let sub2 = ctx.fresh_row_extend(unique_fields2, ext.clone());
+        let ext = solver.ctx.fresh_row_point();
+        let sub1 = solver.ctx.fresh_row_extend(unique_fields1, ext.clone());
+        let sub2 = solver.ctx.fresh_row_extend(unique_fields2, ext.clone());
 
-        unify_rows(ctx, span, &ext1, &sub2)?;
-        unify_rows(ctx, span, &sub1, &ext2)?;
+        unify_rows(solver, span, &ext1, &sub2)?;
+        unify_rows(solver, span, &sub1, &ext2)?;
     };
 
     Ok(())
 }
 
+/// A closed row met fields it does not list; only declared types produce closed rows.
+fn unexpected_fields_error(span: &Span, extra: &Point<RowDescriptor>) -> TypeError {
+    let (fields, _) = gather_fields(BTreeMap::new(), extra);
+    let names = fields.keys().map(|name| format!("'{}'", name)).join(", ");
+    TypeError::RowMismatch {
+        span: *span,
+        message: format!("field {} is not part of the declared type", names),
+    }
+}
+
 fn occurs_row_check(row_point: &Point<RowDescriptor>, desc: &RowDescriptor) -> bool {
     match desc {
-        RowDescriptor::RowFlex(_) => false,
+        RowDescriptor::RowFlex(_) | RowDescriptor::RowEmpty => false,
         RowDescriptor::RowExtend(fields, tail) => {
             // Check if row_point occurs in any field types
             for ty in fields.values() {
@@ -473,7 +801,7 @@ fn occurs_row_check(row_point: &Point<RowDescriptor>, desc: &RowDescriptor) -> b
 fn occurs_in_row_type(row_point: &Point<RowDescriptor>, ty: &Type) -> bool {
     match ty {
         Type::Var(_) => false,
-        Type::Prim(_) => false,
+        Type::Prim(_) | Type::Literal(_) | Type::Named(_) | Type::Error => false,
         Type::Fun(args, res) => {
             for arg in args {
                 if occurs_in_row_type(row_point, arg) {
@@ -482,7 +810,10 @@ fn occurs_in_row_type(row_point: &Point<RowDescriptor>, ty: &Type) -> bool {
             }
             occurs_in_row_type(row_point, res)
         }
-        Type::Array(elem) => occurs_in_row_type(row_point, elem),
+        Type::Array(elem) | Type::Promise(elem) | Type::Dict(elem) => {
+            occurs_in_row_type(row_point, elem)
+        }
+        Type::Tuple(elems) => elems.iter().any(|elem| occurs_in_row_type(row_point, elem)),
         Type::Record(rp) => {
             if rp == row_point {
                 return true;
@@ -490,7 +821,7 @@ fn occurs_in_row_type(row_point: &Point<RowDescriptor>, ty: &Type) -> bool {
             let desc = get_row(rp);
             occurs_row_check(row_point, &desc)
         }
-        Type::DiscriminatedUnion(map) => {
+        Type::DiscriminatedUnion(_, map) => {
             for rp in map.values() {
                 if rp == row_point {
                     return true;
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use itertools::Itertools;
 
@@ -9,30 +9,47 @@ pub type Name = String;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     Prim(String),
+    /// A union of one or more literals of the same kind, e.g. `"idle" | "busy"`
+    Literal(BTreeSet<Literal>),
     Fun(Vec<Type>, Box<Type>),
     Array(Box<Type>),
+    /// An array of known length, e.g. `[a, b]`
+    Tuple(Vec<Type>),
+    Promise(Box<Type>),
+    /// An object with string keys and values of one type, e.g. a map from ids to counts
+    Dict(Box<Type>),
     Var(Point<Descriptor>),
     Record(Point<RowDescriptor>),
-    DiscriminatedUnion(BTreeMap<String, Point<RowDescriptor>>),
+    /// A union of records tagged by a string literal in the named discriminant field.
+    DiscriminatedUnion(Name, BTreeMap<String, Point<RowDescriptor>>),
     View(BTreeMap<Name, Type>),
+    /// A type imported from TypeScript; its structure is left to the TypeScript compiler.
+    Named(Name),
+    /// Stands in for a variable whose constraints failed, so its other uses aren't reported too.
+    Error,
 }
 
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Type::Prim(name) => write!(f, "{}", name),
+            Type::Prim(name) | Type::Named(name) => write!(f, "{}", name),
+            Type::Literal(literals) => write!(f, "{}", literals.iter().join(" | ")),
+            Type::Error => write!(f, "error"),
             Type::Fun(args, ret) => {
                 let arg_strings: Vec<String> = args.iter().map(|arg| format!("{}", arg)).collect();
                 write!(f, "({}) -> {}", arg_strings.join(", "), ret)
             }
             Type::Array(elem) => write!(f, "Array<{}>", elem),
+            Type::Tuple(elems) => write!(f, "[{}]", elems.iter().join(", ")),
+            Type::Promise(value) => write!(f, "Promise<{}>", value),
+            Type::Dict(value) => write!(f, "Record<string, {}>", value),
             Type::Var(point) => write!(f, "{}", point),
             Type::Record(point) => write!(f, "{{{}}}", point),
-            Type::DiscriminatedUnion(map) => {
+            Type::DiscriminatedUnion(tag, map) => {
                 // Render as { type: "a", ...rest } | { type: "b", ...rest } | ...
                 let mut arms: Vec<String> = Vec::new();
                 for (k, rp) in map {
-                    arms.push(format!("{{ type: \"{}\", ...{} }}", k, rp));
+                    arms.push(format!("{{ {}: \"{}\", ...{} }}", tag, k, rp));
                 }
                 write!(f, "{}", arms.join(" | "))
             }
@@ -47,6 +64,35 @@ impl std::fmt::Display for Type {
     }
 }
 
+/// The value of a literal type. Numbers keep their source text, so `2` and `2.0` differ.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Literal {
+    String(String),
+    Number(String),
+    Boolean(bool),
+}
+
+impl Literal {
+    /// The primitive type the literal belongs to
+    pub fn primitive(&self) -> &'static str {
+        match self {
+            Literal::String(_) => "string",
+            Literal::Number(_) => "number",
+            Literal::Boolean(_) => "boolean",
+        }
+    }
+}
+
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::String(value) => write!(f, "\"{}\"", value),
+            Literal::Number(value) => write!(f, "{}", value),
+            Literal::Boolean(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Descriptor {
     Unbound(FlexMark),
@@ -63,9 +109,12 @@ impl std::fmt::Display for Descriptor {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 pub enum RowDescriptor {
     RowExtend(BTreeMap<Name, Type>, Point<RowDescriptor>),
     RowFlex(FlexMark),
+    /// A closed row, admitting no fields beyond those already listed.
+    RowEmpty,
 }
 
 impl std::fmt::Display for RowDescriptor {
@@ -80,6 +129,7 @@ impl std::fmt::Display for RowDescriptor {
                 write!(f, "{}", field_strings.join(", "))
             }
             RowDescriptor::RowFlex(mark) => write!(f, "R{}", mark),
+            RowDescriptor::RowEmpty => write!(f, "R.."),
         }
     }
 }
@@ -99,15 +149,62 @@ impl std::fmt::Display for FlexMark {
     }
 }
 
+/// Where a constraint comes from, used to explain a conflicting type in errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// An attribute of an HTML element or special form, e.g. `condition` of `<if>`
+    Attribute { tag: Name, name: Name },
+    /// An attribute passed to a component, typed by the component's input
+    ComponentParam { view: Name, name: Name },
+    /// The signature of a builtin function
+    Builtin(Name),
+    /// The type or default of a `<param>`
+    Declaration(Name),
+    /// Text content or a string template interpolation
+    Text,
+    /// The structure of an expression, such as a call or a field access
+    Expression,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Attribute { tag, name } => write!(f, "the '{}' attribute of <{}>", name, tag),
+            Origin::ComponentParam { view, name } => {
+                write!(f, "the '{}' parameter of <{}>", name, view)
+            }
+            Origin::Builtin(name) => write!(f, "the builtin '{}'", name),
+            Origin::Declaration(name) => write!(f, "the declaration of '{}'", name),
+            Origin::Text => write!(f, "text interpolation"),
+            Origin::Expression => write!(f, "this expression"),
+        }
+    }
+}
+
+impl Origin {
+    pub fn attribute(tag: &str, name: &str) -> Self {
+        Origin::Attribute {
+            tag: tag.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Constraint {
-    Equal(Span, Type, Type),
+    Equal(Span, Type, Type, Origin),
+    /// A constant's type: its literal where the expected type is a literal type by the time
+    /// the constraint is solved, and its primitive otherwise
+    Constant(Span, Literal, Type, Type),
 }
 
 impl std::fmt::Display for Constraint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Constraint::Equal(_, t1, t2) => write!(f, "{} == {}", t1, t2),
+            Constraint::Equal(_, t1, t2, _) => write!(f, "{} == {}", t1, t2),
+            Constraint::Constant(_, literal, ty, expected) => {
+                write!(f, "{} == constant {} for {}", ty, literal, expected)
+            }
         }
     }
 }
@@ -115,5 +212,5 @@ impl std::fmt::Display for Constraint {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Expected {
     NoExpect,
-    Expect(Type),
+    Expect(Type, Origin),
 }